@@ -1,3 +1,7 @@
+use std::collections::BTreeMap;
+
+use cu::pre::*;
+
 use crate::util;
 
 use super::{emit, parse};
@@ -20,6 +24,11 @@ pub fn build_packages() -> cu::Result<()> {
         builder.add(structure)?;
     }
 
+    let groups_toml_path = registry_path.join("groups.toml");
+    let groups =
+        toml::parse::<BTreeMap<String, emit::GroupDef>>(&cu::fs::read_string(groups_toml_path)?)?;
+    builder.set_groups(groups);
+
     let output = builder.build()?;
     util::write_str_if_modified("registry packages", &output_path, &output)?;
 
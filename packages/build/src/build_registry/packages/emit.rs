@@ -11,14 +11,28 @@ use super::platform::{CfgAttr, Target, TargetSet};
 pub struct RegistryBuilder {
     registry_src_path: PathBuf,
     packages: BTreeMap<String, ModuleFileStructure>,
+    groups: BTreeMap<String, GroupDef>,
 }
+
+/// A package group declared in groups.toml
+#[derive(Deserialize)]
+pub struct GroupDef {
+    pub description: String,
+    /// Kebab-case names of the packages in the group
+    pub packages: Vec<String>,
+}
+
 impl RegistryBuilder {
     pub fn new(path: PathBuf) -> Self {
         Self {
             registry_src_path: path,
             packages: Default::default(),
+            groups: Default::default(),
         }
     }
+    pub fn set_groups(&mut self, groups: BTreeMap<String, GroupDef>) {
+        self.groups = groups;
+    }
     pub fn add(&mut self, structure: ModuleFileStructure) -> cu::Result<()> {
         use std::collections::btree_map::Entry;
         match self.packages.entry(structure.package_name.clone()) {
@@ -114,6 +128,11 @@ impl BinId {
     }
 }"##;
 
+        cu::check!(
+            build_groups(&self.groups, &kebab_pkgs, &pascal_pkgs, &mut out),
+            "failed to generate GroupId enum"
+        )?;
+
         for (snake_name, metadata) in std::iter::zip(&snake_pkgs, &metadatas) {
             for m in metadata {
                 m.build_module(snake_name, &self.registry_src_path, &mut out)?;
@@ -339,15 +358,27 @@ impl ModuleMetadata<'_> {
             "binary_dependencies",
             "empty_bin_set"
         );
+        write_optional_function!(
+            has_binary_dependency_versions,
+            "binary_dependency_versions",
+            "empty_bin_versions"
+        );
         write_optional_function!(
             has_config_dependencies,
             "config_dependencies",
             "empty_pkg_set"
         );
+        write_optional_function!(
+            has_optional_dependencies,
+            "optional_dependencies",
+            "empty_pkg_set"
+        );
+        write_optional_function!(has_conflicts, "conflicts", "empty_pkg_set");
         write_optional_function!(has_download, "download", "ok");
         write_optional_function!(has_configure, "configure", "ok");
         write_optional_function!(has_clean, "clean", "ok");
         write_optional_function!(has_config_location, "config_location", "ok_none");
+        write_optional_function!(has_config_def, "config_def", "none");
         write_optional_function!(has_backup_restore, "backup", "ok");
         write_optional_function!(has_backup_restore, "restore", "ok");
         write_optional_function!(has_pre_uninstall, "pre_uninstall", "ok");
@@ -541,6 +572,23 @@ impl ModuleMetadata<'_> {
             }
             write_end_match!(none);
         }
+        {
+            let _ = writeln!(
+                out,
+                "    pub fn config_def() -> Option<corelib::hmgr::config::UntypedConfigDef> {{ match {mux_match_expr} {{"
+            );
+            for (match_arm, suffix, d) in &mux_arms {
+                if d.has_config_def {
+                    let _ = writeln!(
+                        out,
+                        "        {match_arm} => _pkg_{snake_name}{suffix}::config_def(),",
+                    );
+                } else {
+                    let _ = writeln!(out, "        {match_arm} => None,");
+                }
+            }
+            write_end_match!(default);
+        }
         for fn_name in ["verify", "install", "uninstall"] {
             let retty = if fn_name == "verify" {
                 "crate::Verified"
@@ -614,11 +662,22 @@ impl ModuleMetadata<'_> {
             has_binary_dependencies,
             "enumset::EnumSet<super::BinId>"
         );
+        write_dependency_function!(
+            "binary_dependency_versions",
+            has_binary_dependency_versions,
+            "&'static [(super::BinId, &'static str)]"
+        );
         write_dependency_function!(
             "config_dependencies",
             has_config_dependencies,
             "enumset::EnumSet<super::PkgId>"
         );
+        write_dependency_function!(
+            "optional_dependencies",
+            has_optional_dependencies,
+            "enumset::EnumSet<super::PkgId>"
+        );
+        write_dependency_function!("conflicts", has_conflicts, "enumset::EnumSet<super::PkgId>");
 
         let _ = writeln!(out, "}}");
 
@@ -702,6 +761,57 @@ impl std::fmt::Display for {prefix}Id {{
     Ok(())
 }
 
+fn build_groups(
+    groups: &BTreeMap<String, GroupDef>,
+    kebab_pkgs: &[&String],
+    pascal_pkgs: &[String],
+    out: &mut String,
+) -> cu::Result<()> {
+    use std::fmt::Write as _;
+
+    let kebab_groups = groups.keys().collect::<Vec<_>>();
+    let (pascal_groups, _) = kebab::generate_casings_from_kebab(&kebab_groups);
+    generate_id_enum(out, "Group", &kebab_groups, &pascal_groups)?;
+
+    writeln!(out, "impl GroupId {{")?;
+    writeln!(
+        out,
+        "    /// Get the description of the group\n    pub fn description(self) -> &'static str {{ match self {{"
+    )?;
+    for (group, pascal_group) in std::iter::zip(groups.values(), &pascal_groups) {
+        writeln!(
+            out,
+            "            Self::{pascal_group} => {:?},",
+            group.description
+        )?;
+    }
+    writeln!(out, "        }}\n    }}")?;
+    writeln!(
+        out,
+        "    /// Get the packages in the group that are enabled on the current platform\n    pub fn packages(self) -> EnumSet<PkgId> {{ let pkgs: EnumSet<PkgId> = match self {{"
+    )?;
+    for ((kebab_group, group), pascal_group) in std::iter::zip(groups, &pascal_groups) {
+        let mut members = Vec::with_capacity(group.packages.len());
+        for pkg in &group.packages {
+            let Some(i) = kebab_pkgs.iter().position(|x| *x == pkg) else {
+                cu::bail!("package '{pkg}' in group '{kebab_group}' does not exist");
+            };
+            members.push(format!("PkgId::{}", pascal_pkgs[i]));
+        }
+        writeln!(
+            out,
+            "            Self::{pascal_group} => enum_set! {{ {} }},",
+            members.join(" | ")
+        )?;
+    }
+    writeln!(
+        out,
+        "        }}; pkgs.iter().filter(|x| x.package().enabled()).collect() }}\n}}"
+    )?;
+
+    Ok(())
+}
+
 fn build_bin_providers(
     pascal_bins: &[String],
     kebab_bins: &[&String],
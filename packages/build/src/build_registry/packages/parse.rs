@@ -205,11 +205,15 @@ pub struct ModuleData {
     pub doc: Vec<String>,
     pub kebab_binaries: BTreeSet<String>,
     pub has_binary_dependencies: bool,
+    pub has_binary_dependency_versions: bool,
     pub has_config_dependencies: bool,
+    pub has_optional_dependencies: bool,
+    pub has_conflicts: bool,
     pub has_download: bool,
     pub has_configure: bool,
     pub has_clean: bool,
     pub has_config_location: bool,
+    pub has_config_def: bool,
     pub has_backup_restore: bool,
     pub has_pre_uninstall: bool,
 }
@@ -251,12 +255,20 @@ impl cu::Parse for ModuleData {
                         }
                         "binary_dependencies" => {
                             export_idents.push("binary_dependencies".to_string());
+                            export_idents.push("binary_dependency_versions".to_string());
                         }
                         "config_dependencies" => {
                             export_idents.push("config_dependencies".to_string());
                         }
+                        "optional_dependencies" => {
+                            export_idents.push("optional_dependencies".to_string());
+                        }
+                        "conflicts" => {
+                            export_idents.push("conflicts".to_string());
+                        }
                         "config_file" => {
                             export_idents.push("config_location".to_string());
+                            export_idents.push("config_def".to_string());
                         }
                         _ => {}
                     }
@@ -279,22 +291,30 @@ impl cu::Parse for ModuleData {
         }
 
         let mut has_binary_dependencies = false;
+        let mut has_binary_dependency_versions = false;
         let mut has_config_dependencies = false;
+        let mut has_optional_dependencies = false;
+        let mut has_conflicts = false;
         let mut has_download = false;
         let mut has_configure = false;
         let mut has_clean = false;
         let mut has_config_location = false;
+        let mut has_config_def = false;
         let mut has_backup = false;
         let mut has_restore = false;
         let mut has_pre_uninstall = false;
         for ident in export_idents {
             match ident.as_str() {
                 "binary_dependencies" => has_binary_dependencies = true,
+                "binary_dependency_versions" => has_binary_dependency_versions = true,
                 "config_dependencies" => has_config_dependencies = true,
+                "optional_dependencies" => has_optional_dependencies = true,
+                "conflicts" => has_conflicts = true,
                 "download" => has_download = true,
                 "configure" => has_configure = true,
                 "clean" => has_clean = true,
                 "config_location" => has_config_location = true,
+                "config_def" => has_config_def = true,
                 "backup" => has_backup = true,
                 "restore" => has_restore = true,
                 "pre_uninstall" => has_pre_uninstall = true,
@@ -313,11 +333,15 @@ impl cu::Parse for ModuleData {
             doc,
             kebab_binaries: binaries,
             has_binary_dependencies,
+            has_binary_dependency_versions,
             has_config_dependencies,
+            has_optional_dependencies,
+            has_conflicts,
             has_download,
             has_configure,
             has_clean,
             has_config_location,
+            has_config_def,
             has_backup_restore: has_backup,
             has_pre_uninstall,
         })
@@ -6,6 +6,9 @@ use super::{BinId, PkgId};
 pub fn empty_bin_set() -> EnumSet<BinId> {
     Default::default()
 }
+pub fn empty_bin_versions() -> &'static [(BinId, &'static str)] {
+    &[]
+}
 pub fn empty_pkg_set() -> EnumSet<PkgId> {
     Default::default()
 }
@@ -19,6 +22,9 @@ pub fn unsupported_platform<T>(_: &Context) -> cu::Result<T> {
 pub fn ok_none<T>(_: &Context) -> cu::Result<Option<T>> {
     Ok(None)
 }
+pub fn none<T>() -> Option<T> {
+    None
+}
 impl crate::Package {
     /// Create a stub package definition, used to fill spots in the registry array
     /// for unsupported platforms
@@ -36,11 +42,15 @@ impl crate::Package {
             install_fn: unsupported_platform,
             uninstall_fn: unsupported_platform,
             binary_dependencies_fn: empty_bin_set,
+            binary_dependency_versions_fn: empty_bin_versions,
             config_dependencies_fn: empty_pkg_set,
+            optional_dependencies_fn: empty_pkg_set,
+            conflicts_fn: empty_pkg_set,
             download_fn: ok,
             configure_fn: ok,
             clean_fn: ok,
             config_location_fn: ok_none,
+            config_def_fn: none,
             backup_fn: ok,
             restore_fn: ok,
             pre_uninstall_fn: ok,
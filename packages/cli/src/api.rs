@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use corelib::{hmgr, opfs};
 use cu::pre::*;
 
@@ -23,12 +25,29 @@ pub struct CliApi {
     /// Same as the version subcommand, -v to run self-check
     #[clap(short = 'V', long)]
     version: bool,
+
+    /// Never prompt: use the default answer if there is one, otherwise fail.
+    /// Same as one --non-interactive, and can also be set with SHAFT_NO_INPUT=1.
+    /// SHAFT_YES=1 can be used for --yes
+    #[clap(long, global = true)]
+    no_input: bool,
 }
 impl CliApi {
     pub fn preprocess(&mut self) {
         if let Some(command) = &self.command {
             self.flags.merge(command.as_ref());
         }
+        if crate::interactive::env_flag(crate::interactive::ENV_YES) {
+            self.flags.yes = true;
+        }
+        if self.no_input || crate::interactive::env_flag(crate::interactive::ENV_NO_INPUT) {
+            self.flags.non_interactive = self.flags.non_interactive.max(1);
+        }
+        crate::interactive::init(
+            self.flags.yes,
+            self.flags.non_interactive,
+            self.flags.interactive,
+        );
     }
     pub fn run(self) -> cu::Result<()> {
         let run_version = self.version || matches!(&self.command, Some(CliCommand::Version(_)));
@@ -54,7 +73,17 @@ impl CliApi {
             "core requirements not satisfied"
         )?;
         cu::check!(opfs::init(), "failed to init platform")?;
+        if let Some(CliCommand::Doctor(_)) = &self.command {
+            // doctor checks the home and the environment by itself,
+            // and should not be blocked by failures in them or by the lock
+            return crate::cmds::doctor();
+        }
         cu::check!(crate::init::check_init_home(), "failed to init home")?;
+        if let Some(CliCommand::Status(cmd)) = &self.command {
+            // status only reads the install cache, and should not be blocked
+            // by the lock while another shaft process is running
+            return cmd.run();
+        }
         let config = crate::config::load_config()?;
         cu::check!(
             crate::init::check_init_environment(&config),
@@ -84,8 +113,28 @@ impl CliApi {
         };
 
         let _lock = hmgr::lock()?;
+        // only commands that change packages need to recover an interrupted sync
+        if matches!(
+            &command,
+            CliCommand::Sync(_) | CliCommand::Remove(_) | CliCommand::Apply(_)
+        ) {
+            let resuming = matches!(&command, CliCommand::Sync(x) if x.resume);
+            let dry_run = match &command {
+                CliCommand::Sync(x) => x.dry_run,
+                CliCommand::Remove(x) => x.dry_run,
+                _ => false,
+            };
+            if crate::graph::check_interrupted_sync(dry_run)? && !resuming {
+                cu::hint!("run `shaft sync --resume` to continue the interrupted sync");
+            }
+        }
 
-        command.run()
+        let operation = command.history_operation();
+        let result = command.run();
+        if let Some(operation) = operation {
+            crate::history::append(operation, &result);
+        }
+        result
     }
 }
 
@@ -97,12 +146,34 @@ pub enum CliCommand {
     Sync(CliCommandSync),
     /// Remove package(s)
     Remove(CliCommandRemove),
+    /// Remove packages installed as dependencies that are no longer needed
+    Autoremove(CliCommandAutoremove),
+    /// Roll back a package to its previous installation
+    Rollback(CliCommandRollback),
+    /// Hold package(s) at the installed version when syncing all packages
+    Hold(CliCommandHold),
+    /// Release the hold on package(s)
+    Unhold(CliCommandHold),
+    /// Install, remove and configure packages to match a manifest
+    Apply(CliCommandApply),
     /// Edit configuration for a package
     Config(CliCommandConfig),
     /// Search or print info of a package or binary
     Info(CliCommandInfo),
+    /// Explain why a package or binary is installed
+    Why(CliCommandWhy),
+    /// Print the dependency graph of package(s)
+    Graph(CliCommandGraph),
+    /// Print the history of operations that changed the machine
+    Log(CliCommandLog),
     /// Clean temporary files for this tool and/or package(s)
     Clean(CliCommandClean),
+    /// Check installed packages without changing anything
+    Status(CliCommandStatus),
+    /// Print the installed and new versions of packages to be upgraded
+    Outdated(CliCommandOutdated),
+    /// Check the environment and print fixes for problems found
+    Doctor(cu::cli::Flags),
     /// Print the version, -v to run self-check
     Version(cu::cli::Flags),
 }
@@ -112,23 +183,67 @@ impl AsRef<cu::cli::Flags> for CliCommand {
             CliCommand::Upgrade(x) => x.as_ref(),
             CliCommand::Sync(x) => x.as_ref(),
             CliCommand::Remove(x) => x.as_ref(),
+            CliCommand::Autoremove(x) => x.as_ref(),
+            CliCommand::Rollback(x) => x.as_ref(),
+            CliCommand::Hold(x) => x.as_ref(),
+            CliCommand::Unhold(x) => x.as_ref(),
+            CliCommand::Apply(x) => x.as_ref(),
             CliCommand::Config(x) => x.as_ref(),
             CliCommand::Clean(x) => x.as_ref(),
             CliCommand::Info(x) => x.as_ref(),
+            CliCommand::Why(x) => x.as_ref(),
+            CliCommand::Graph(x) => x.as_ref(),
+            CliCommand::Log(x) => x.as_ref(),
+            CliCommand::Status(x) => x.as_ref(),
+            CliCommand::Outdated(x) => x.as_ref(),
+            CliCommand::Doctor(x) => x,
             CliCommand::Version(x) => x,
         }
     }
 }
 impl CliCommand {
+    /// Name of the operation to record in the history,
+    /// `None` if the command does not change anything
+    fn history_operation(&self) -> Option<&'static str> {
+        match self {
+            CliCommand::Upgrade(_) => Some("upgrade"),
+            CliCommand::Sync(x) if !x.dry_run => Some("sync"),
+            CliCommand::Remove(x) if !x.dry_run => Some("remove"),
+            CliCommand::Autoremove(x) if !x.dry_run => Some("autoremove"),
+            CliCommand::Rollback(_) => Some("rollback"),
+            CliCommand::Hold(_) => Some("hold"),
+            CliCommand::Unhold(_) => Some("unhold"),
+            CliCommand::Apply(_) => Some("apply"),
+            CliCommand::Config(x)
+                if !x.location
+                    && !x.diff
+                    && !matches!(x.action, Some(ConfigAction::Get { .. })) =>
+            {
+                Some("config")
+            }
+            CliCommand::Clean(_) => Some("clean"),
+            _ => None,
+        }
+    }
     pub fn run(self) -> cu::Result<()> {
         match self {
-            CliCommand::Version(_) => {}
+            CliCommand::Version(_) | CliCommand::Doctor(_) => {}
             CliCommand::Upgrade(cmd) => cmd.run()?,
             CliCommand::Sync(cmd) => cmd.run()?,
             CliCommand::Remove(cmd) => cmd.run()?,
+            CliCommand::Autoremove(cmd) => crate::cmds::autoremove(cmd.dry_run)?,
+            CliCommand::Rollback(cmd) => cmd.run()?,
+            CliCommand::Hold(cmd) => crate::cmds::hold(&cmd.packages, true)?,
+            CliCommand::Unhold(cmd) => crate::cmds::hold(&cmd.packages, false)?,
+            CliCommand::Apply(cmd) => cmd.run()?,
             CliCommand::Config(cmd) => cmd.run()?,
             CliCommand::Info(cmd) => cmd.run()?,
+            CliCommand::Why(cmd) => cmd.run()?,
+            CliCommand::Graph(cmd) => cmd.run()?,
+            CliCommand::Log(cmd) => cmd.run()?,
             CliCommand::Clean(cmd) => cmd.run()?,
+            CliCommand::Status(cmd) => cmd.run()?,
+            CliCommand::Outdated(cmd) => cmd.run()?,
         }
         Ok(())
     }
@@ -149,32 +264,95 @@ impl CliCommandUpgrade {
 
 #[derive(clap::Parser, Debug, AsRef)]
 pub struct CliCommandSync {
-    /// Package(s) to install or update. If none specified, will update all installed packages.
+    /// Package(s) to install or update, `@<group>` for a package group. If none specified, will update all installed packages.
     pub packages: Vec<String>,
+    /// Select the package to provide a binary, in the format of `<binary>=<package>`.
+    /// The selection is saved in the config
+    #[clap(long = "provider", value_name = "BIN=PKG")]
+    pub providers: Vec<String>,
+    /// Print the sync plan without changing anything
+    #[clap(long)]
+    pub dry_run: bool,
+    /// Continue the last sync that was interrupted
+    #[clap(long, conflicts_with_all = ["packages", "providers", "dry_run"])]
+    pub resume: bool,
     #[clap(flatten)]
     #[as_ref]
     pub flags: cu::cli::Flags,
 }
 impl CliCommandSync {
     fn run(&self) -> cu::Result<()> {
-        crate::cmds::sync(&self.packages)
+        crate::cmds::sync(&self.packages, &self.providers, self.dry_run, self.resume)
     }
 }
 
 #[derive(clap::Parser, Debug, AsRef)]
 pub struct CliCommandRemove {
-    /// Package(s) to remove.
+    /// Package(s) to remove, `@<group>` for a package group.
     pub packages: Vec<String>,
     /// Force uninstall when package is in an unclean state.
     #[clap(short, long)]
     pub force: bool,
+    /// Print the remove plan without changing anything
+    #[clap(long)]
+    pub dry_run: bool,
+    /// Also remove dependencies no longer needed by any explicitly installed package
+    #[clap(long)]
+    pub recursive: bool,
     #[clap(flatten)]
     #[as_ref]
     pub flags: cu::cli::Flags,
 }
 impl CliCommandRemove {
     fn run(&self) -> cu::Result<()> {
-        crate::cmds::remove(&self.packages, self.force)
+        crate::cmds::remove(&self.packages, self.force, self.dry_run, self.recursive)
+    }
+}
+
+#[derive(clap::Parser, Debug, AsRef)]
+pub struct CliCommandAutoremove {
+    /// Print the remove plan without changing anything
+    #[clap(long)]
+    pub dry_run: bool,
+    #[clap(flatten)]
+    #[as_ref]
+    pub flags: cu::cli::Flags,
+}
+
+#[derive(clap::Parser, Debug, AsRef)]
+pub struct CliCommandRollback {
+    /// Package to roll back. Rolling back again goes back to the installation before rollback
+    pub package: String,
+    #[clap(flatten)]
+    #[as_ref]
+    pub flags: cu::cli::Flags,
+}
+impl CliCommandRollback {
+    fn run(&self) -> cu::Result<()> {
+        crate::cmds::rollback(&self.package)
+    }
+}
+
+#[derive(clap::Parser, Debug, AsRef)]
+pub struct CliCommandHold {
+    /// Package(s) to hold or unhold
+    pub packages: Vec<String>,
+    #[clap(flatten)]
+    #[as_ref]
+    pub flags: cu::cli::Flags,
+}
+
+#[derive(clap::Parser, Debug, AsRef)]
+pub struct CliCommandApply {
+    /// Path to the manifest (shaft.toml)
+    pub manifest: PathBuf,
+    #[clap(flatten)]
+    #[as_ref]
+    pub flags: cu::cli::Flags,
+}
+impl CliCommandApply {
+    fn run(&self) -> cu::Result<()> {
+        crate::cmds::apply(&self.manifest)
     }
 }
 
@@ -188,13 +366,56 @@ pub struct CliCommandConfig {
     /// Just mark the config as dirty instead of editing
     #[clap(short, long)]
     pub dirty: bool,
+    /// Print the values that are different from the defaults, and unknown keys
+    #[clap(long, conflicts_with_all = ["location", "dirty", "reset"])]
+    pub diff: bool,
+    /// Reset the config to the defaults, or only the dotted key if specified
+    #[clap(long, value_name = "KEY", conflicts_with_all = ["location", "dirty"])]
+    pub reset: Option<Option<String>>,
+    /// Get or set a single config key instead of editing
+    #[clap(subcommand)]
+    pub action: Option<ConfigAction>,
     #[clap(flatten)]
     #[as_ref]
     pub flags: cu::cli::Flags,
 }
+#[derive(clap::Subcommand, Debug)]
+pub enum ConfigAction {
+    /// Print the value of a config key
+    Get {
+        /// Dotted path of the key, for example `section.key`
+        key: String,
+    },
+    /// Set the value of a config key, and mark the config as dirty.
+    /// The value must have the same type as the default
+    Set {
+        /// Dotted path of the key, for example `section.key`
+        key: String,
+        /// The value in TOML. Strings can be specified without quotes
+        value: String,
+    },
+}
 impl CliCommandConfig {
     fn run(&self) -> cu::Result<()> {
         cu::lv::disable_print_time();
+        if let Some(action) = &self.action {
+            let Some(package) = &self.package else {
+                cu::bail!("please specify a package name");
+            };
+            return match action {
+                ConfigAction::Get { key } => crate::cmds::config_get(package, key),
+                ConfigAction::Set { key, value } => crate::cmds::config_set(package, key, value),
+            };
+        }
+        if self.diff || self.reset.is_some() {
+            let Some(package) = &self.package else {
+                cu::bail!("please specify a package name");
+            };
+            return match &self.reset {
+                Some(key) => crate::cmds::config_reset(package, key.as_deref()),
+                None => crate::cmds::config_diff(package),
+            };
+        }
         if self.location {
             let Some(package) = &self.package else {
                 cu::bail!("please specify a package name");
@@ -219,7 +440,7 @@ impl CliCommandConfig {
 
 #[derive(clap::Parser, Debug, AsRef)]
 pub struct CliCommandInfo {
-    /// Package or binary. Use --search to search. Must be provided if --installed is false
+    /// Package, binary, or `@<group>`. Use --search to search. Must be provided if --installed is false
     ///
     /// Specify --binary or --package to narrow the type to search
     pub pkg_or_bin_query: Option<String>,
@@ -241,16 +462,27 @@ pub struct CliCommandInfo {
     pub installed: bool,
 
     /// Machine mode. Only print the name of the package, one per line
-    #[clap(short, long)]
+    #[clap(short, long, conflicts_with = "json")]
     pub machine: bool,
 
+    /// JSON mode. Print structured records of the packages as a JSON array
+    #[clap(long, conflicts_with = "machine")]
+    pub json: bool,
+
     #[clap(flatten)]
     #[as_ref]
     pub flags: cu::cli::Flags,
 }
 impl CliCommandInfo {
     fn run(self) -> cu::Result<()> {
-        if self.machine {
+        let output = if self.json {
+            crate::cmds::InfoOutput::Json
+        } else if self.machine {
+            crate::cmds::InfoOutput::Machine
+        } else {
+            crate::cmds::InfoOutput::Pretty
+        };
+        if output != crate::cmds::InfoOutput::Pretty {
             cu::lv::disable_print_time();
         }
         let result = crate::cmds::info(
@@ -259,7 +491,7 @@ impl CliCommandInfo {
             self.installed,
             self.binary,
             self.package,
-            self.machine,
+            output,
         );
         let found = cu::check!(result, "error getting package information")?;
         if !found {
@@ -269,6 +501,43 @@ impl CliCommandInfo {
     }
 }
 
+#[derive(clap::Parser, Debug, AsRef)]
+pub struct CliCommandWhy {
+    /// Package or binary to explain
+    pub pkg_or_bin: String,
+
+    #[clap(flatten)]
+    #[as_ref]
+    pub flags: cu::cli::Flags,
+}
+
+impl CliCommandWhy {
+    fn run(self) -> cu::Result<()> {
+        crate::cmds::why(&self.pkg_or_bin)
+    }
+}
+
+#[derive(clap::Parser, Debug, AsRef)]
+pub struct CliCommandGraph {
+    /// Package(s) to include in the graph, along with their dependencies.
+    /// If none specified, will include the whole registry.
+    pub packages: Vec<String>,
+
+    /// Output format
+    #[clap(long, value_enum, default_value_t = crate::cmds::GraphFormat::Dot)]
+    pub format: crate::cmds::GraphFormat,
+
+    #[clap(flatten)]
+    #[as_ref]
+    pub flags: cu::cli::Flags,
+}
+
+impl CliCommandGraph {
+    fn run(self) -> cu::Result<()> {
+        crate::cmds::graph(&self.packages, self.format)
+    }
+}
+
 #[derive(clap::Parser, Debug, AsRef)]
 pub struct CliCommandClean {
     /// Package(s) to clean. If none specified, will only clean 'core' (shaft itself).
@@ -288,3 +557,54 @@ impl CliCommandClean {
         crate::cmds::clean(&self.package, self.all)
     }
 }
+
+#[derive(clap::Parser, Debug, AsRef)]
+pub struct CliCommandStatus {
+    /// Also check all packages in the registry for ones installed outside of shaft.
+    /// This is slow, since every package is verified
+    #[clap(long)]
+    pub untracked: bool,
+
+    #[clap(flatten)]
+    #[as_ref]
+    pub flags: cu::cli::Flags,
+}
+
+impl CliCommandStatus {
+    fn run(&self) -> cu::Result<()> {
+        crate::cmds::status(self.untracked)
+    }
+}
+
+#[derive(clap::Parser, Debug, AsRef)]
+pub struct CliCommandOutdated {
+    /// Print all installed packages, including ones that are up to date
+    #[clap(short, long)]
+    pub all: bool,
+
+    #[clap(flatten)]
+    #[as_ref]
+    pub flags: cu::cli::Flags,
+}
+
+impl CliCommandOutdated {
+    fn run(self) -> cu::Result<()> {
+        crate::cmds::outdated(self.all)
+    }
+}
+
+#[derive(clap::Parser, Debug, AsRef)]
+pub struct CliCommandLog {
+    /// Only show operations that touched the package
+    pub package: Option<String>,
+
+    #[clap(flatten)]
+    #[as_ref]
+    pub flags: cu::cli::Flags,
+}
+
+impl CliCommandLog {
+    fn run(self) -> cu::Result<()> {
+        crate::cmds::log(self.package.as_deref())
+    }
+}
@@ -0,0 +1,97 @@
+use std::path::Path;
+
+use corelib::hmgr;
+use cu::pre::*;
+use enum_map::EnumMap;
+use enumset::EnumSet;
+use itertools::Itertools as _;
+use registry::{BinId, PkgId};
+
+use crate::graph::{self, InstallCache};
+use crate::manifest::Manifest;
+
+/// Converge the machine to the state declared in the manifest
+pub fn apply(manifest_path: &Path) -> cu::Result<()> {
+    let manifest = Manifest::load(manifest_path)?;
+    let mut providers = manifest.providers;
+    let installed = InstallCache::load()?;
+    graph::preselect_providers(&mut providers, &installed)?;
+
+    // remove the packages not required by the manifest
+    let required = collect_required(manifest.pkgs, &installed, &mut providers)?;
+    let mut to_remove = installed.pkgs.difference(required);
+    to_remove.remove(PkgId::Core);
+    if !to_remove.is_empty() {
+        let pkgs_string = to_remove.iter().join(", ");
+        cu::warn!("installed packages not in the manifest: [ {pkgs_string} ]");
+        if crate::interactive::yesno(
+            "remove them?",
+            "rerun with --yes to remove them, or add them to the manifest",
+        )? {
+            let names = to_remove.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+            cu::check!(
+                super::remove(&names, false, false, false),
+                "failed to remove packages not in the manifest"
+            )?;
+        } else {
+            cu::warn!("keeping packages not in the manifest");
+        }
+    }
+
+    let mut installed = InstallCache::load()?;
+    for (pkg, values) in &manifest.configs {
+        let Some(config_def) = pkg.package().config_def() else {
+            continue;
+        };
+        let path = hmgr::paths::config_file(pkg.to_str());
+        let changed = cu::check!(
+            config_def.merge(&path, values),
+            "failed to apply config overrides for '{pkg}'"
+        )?;
+        if changed {
+            cu::info!("applied config overrides for '{pkg}'");
+            if installed.pkgs.contains(*pkg) {
+                installed.set_dirty(*pkg, true);
+            }
+        }
+    }
+    installed.save()?;
+
+    installed.requested = manifest.pkgs;
+    let edited = installed
+        .pkgs
+        .iter()
+        .filter(|x| installed.is_config_edited(*x))
+        .collect::<EnumSet<_>>();
+    let pkgs = manifest.pkgs.difference(installed.pkgs) | installed.dirty | edited;
+    if pkgs.is_empty() {
+        cu::info!("all packages in the manifest are installed");
+        return Ok(());
+    }
+    super::sync_pkgs_with_providers(pkgs, &mut installed, providers)
+}
+
+/// Collect the packages and their binary dependencies, selecting
+/// the providers for the binaries along the way
+fn collect_required(
+    pkgs: EnumSet<PkgId>,
+    installed: &InstallCache,
+    provider_selection: &mut EnumMap<BinId, Option<PkgId>>,
+) -> cu::Result<EnumSet<PkgId>> {
+    let mut out = EnumSet::new();
+    let mut stack = pkgs.iter().collect::<Vec<_>>();
+    while let Some(pkg) = stack.pop() {
+        if !out.insert(pkg) {
+            continue;
+        }
+        for bin_id in pkg.package().binary_dependencies() {
+            stack.push(graph::select_dependency_provider(
+                pkg,
+                bin_id,
+                provider_selection,
+                installed,
+            )?);
+        }
+    }
+    Ok(out)
+}
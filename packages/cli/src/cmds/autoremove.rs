@@ -0,0 +1,29 @@
+use enumset::EnumSet;
+use itertools::Itertools as _;
+
+use crate::graph::{self, InstallCache};
+
+/// Remove the packages that were installed as dependencies,
+/// and are no longer needed by any explicitly installed package
+pub fn autoremove(dry_run: bool) -> cu::Result<()> {
+    let installed = InstallCache::load()?;
+    let orphans = graph::find_orphans(&installed, EnumSet::new());
+    if orphans.is_empty() {
+        cu::info!("no package to remove");
+        return Ok(());
+    }
+    let pkgs_string = orphans.iter().join(", ");
+    cu::info!("packages no longer needed: [ {pkgs_string} ]");
+    let names = orphans.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+    if dry_run {
+        return super::remove(&names, false, true, false);
+    }
+    if !crate::interactive::yesno(
+        "remove them?",
+        "rerun with --yes to remove them, or with --dry-run to only list them",
+    )? {
+        cu::info!("keeping packages no longer needed");
+        return Ok(());
+    }
+    super::remove(&names, false, false, false)
+}
@@ -32,6 +32,7 @@ pub fn clean(packages: &[String], all: bool) -> cu::Result<()> {
             let bar = cu::progress(format!("clean '{pkg}'")).spawn();
             ctx.set_bar(Some(&bar));
             ctx.stage.set(Stage::Clean);
+            crate::history::record_package(pkg, "clean", None);
             if let Err(e) = pkg.package().clean(&ctx) {
                 cu::warn!("failed to clean package {pkg}: {e:?}");
             }
@@ -40,6 +41,7 @@ pub fn clean(packages: &[String], all: bool) -> cu::Result<()> {
     }
 
     if clean_core {
+        crate::history::record_package(PkgId::Core, "clean", None);
         corelib::hmgr::clean_home();
     }
 
@@ -1,5 +1,6 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use corelib::hmgr;
 use cu::pre::*;
 use registry::{Context, PkgId, Stage};
 
@@ -8,13 +9,8 @@ use crate::graph::InstallCache;
 pub fn config(package: &str) -> cu::Result<()> {
     let pkg = cu::check!(PkgId::from_str(package), "cannot find package '{package}'")?;
     let config_location = config_location_path(pkg)?;
-    let mut installed = InstallCache::load()?;
-    installed.set_dirty(pkg, true);
-    cu::check!(
-        installed.save(),
-        "failed to mark configuration for '{pkg}' as dirty"
-    )?;
     if config_location.is_dir() {
+        mark_dirty(pkg, "edit config")?;
         cu::hint!(
             r"the config location for '{pkg}' is a directory.
 you can print the path with
@@ -29,7 +25,31 @@ after editing the config, please run `shaft sync`
     }
     let content = cu::fs::read_string(&config_location).ok();
     shutil_viopen::open(&config_location)?;
-    let content_after = cu::fs::read_string(&config_location).ok();
+    let mut content_after = cu::fs::read_string(&config_location).ok();
+    if let Some(config_def) = pkg.package().config_def() {
+        while let Some(new_content) = &content_after {
+            let Err(e) = config_def.validate(new_content) else {
+                break;
+            };
+            cu::error!("invalid config for '{pkg}': {e:?}");
+            if crate::interactive::is_unattended() {
+                revert_config(pkg, &config_location, content.as_deref())?;
+                cu::bail!("config for '{pkg}' is invalid, reverted to the previous content");
+            }
+            if cu::yesno!("re-open the editor to fix it?")? {
+                shutil_viopen::open(&config_location)?;
+                content_after = cu::fs::read_string(&config_location).ok();
+                continue;
+            }
+            if cu::yesno!("revert to the previous content?")? {
+                revert_config(pkg, &config_location, content.as_deref())?;
+                cu::info!("reverted config for '{pkg}'");
+                return Ok(());
+            }
+            cu::bail!("config for '{pkg}' is invalid, please fix it before running `shaft sync`");
+        }
+    }
+    mark_dirty(pkg, "edit config")?;
     if content.is_some() && content == content_after {
         cu::info!("no change");
         return Ok(());
@@ -38,14 +58,142 @@ after editing the config, please run `shaft sync`
     Ok(())
 }
 
-pub fn config_dirty(package: &str) -> cu::Result<()> {
-    let pkg = cu::check!(PkgId::from_str(package), "cannot find package '{package}'")?;
+fn mark_dirty(pkg: PkgId, action: &str) -> cu::Result<()> {
     let mut installed = InstallCache::load()?;
     installed.set_dirty(pkg, true);
     cu::check!(
         installed.save(),
         "failed to mark configuration for '{pkg}' as dirty"
     )?;
+    crate::history::record_package(pkg, action, None);
+    Ok(())
+}
+
+/// Restore the config file to the content before editing,
+/// or delete it if it didn't exist
+fn revert_config(pkg: PkgId, path: &Path, content: Option<&str>) -> cu::Result<()> {
+    match content {
+        Some(content) => cu::check!(
+            cu::fs::write(path, content),
+            "failed to revert config for '{pkg}'"
+        ),
+        None => cu::check!(cu::fs::remove(path), "failed to revert config for '{pkg}'"),
+    }
+}
+
+/// Print the value of the dotted key in the config of the package
+pub fn config_get(package: &str, key: &str) -> cu::Result<()> {
+    let pkg = cu::check!(PkgId::from_str(package), "cannot find package '{package}'")?;
+    let config_def = cu::check!(
+        pkg.package().config_def(),
+        "package '{pkg}' does not have a config definition"
+    )?;
+    let path = hmgr::paths::config_file(pkg.to_str());
+    let value = cu::check!(
+        config_def.get_value(&path, key),
+        "failed to get config '{key}' for '{pkg}'"
+    )?;
+    match value {
+        toml::Value::String(x) => println!("{x}"),
+        x => println!("{}", hmgr::config::serialize_value(&x)),
+    }
+    Ok(())
+}
+
+/// Set the value of the dotted key in the config of the package, and mark it dirty
+pub fn config_set(package: &str, key: &str, value: &str) -> cu::Result<()> {
+    let pkg = cu::check!(PkgId::from_str(package), "cannot find package '{package}'")?;
+    let config_def = cu::check!(
+        pkg.package().config_def(),
+        "package '{pkg}' does not have a config definition"
+    )?;
+    let path = hmgr::paths::config_file(pkg.to_str());
+    let changed = cu::check!(
+        config_def.set_value(&path, key, value),
+        "failed to set config '{key}' for '{pkg}'"
+    )?;
+    if !changed {
+        cu::info!("no change");
+        return Ok(());
+    }
+    mark_dirty(pkg, &format!("set config {key}"))?;
+    cu::info!("set '{key}' for '{pkg}'");
+    cu::hint!(r"after done configuring, please run `shaft sync`");
+    Ok(())
+}
+
+/// Print the values in the config of the package that are different from the defaults,
+/// and the unknown keys
+pub fn config_diff(package: &str) -> cu::Result<()> {
+    let pkg = cu::check!(PkgId::from_str(package), "cannot find package '{package}'")?;
+    let config_def = cu::check!(
+        pkg.package().config_def(),
+        "package '{pkg}' does not have a config definition"
+    )?;
+    let path = hmgr::paths::config_file(pkg.to_str());
+    let (diffs, (unknown_count, unknown_repr)) =
+        cu::check!(config_def.diff(&path), "failed to diff config for '{pkg}'")?;
+    if diffs.is_empty() {
+        cu::info!("all values are the defaults");
+    } else {
+        let rows = diffs
+            .into_iter()
+            .map(|x| {
+                let value = hmgr::config::serialize_value(&x.value);
+                let default = hmgr::config::serialize_value(&x.default);
+                (x.key, value, default)
+            })
+            .collect::<Vec<_>>();
+        let key_width = rows.iter().map(|x| x.0.len()).max().unwrap_or(3).max(3);
+        let value_width = rows.iter().map(|x| x.1.len()).max().unwrap_or(5).max(5);
+        cu::hint!(
+            "{:>key_width$} | {:>value_width$} | default\n------------------------------------------------------------",
+            "key",
+            "value"
+        );
+        for (key, value, default) in rows {
+            cu::print!("{key:>key_width$} | {value:>value_width$} | {default}");
+        }
+    }
+    if unknown_count > 0 {
+        cu::warn!("there were {unknown_count} unknown config keys:\n{unknown_repr}");
+    }
+    Ok(())
+}
+
+/// Reset the config of the package to the defaults, or only the dotted key, and mark it dirty
+pub fn config_reset(package: &str, key: Option<&str>) -> cu::Result<()> {
+    let pkg = cu::check!(PkgId::from_str(package), "cannot find package '{package}'")?;
+    let config_def = cu::check!(
+        pkg.package().config_def(),
+        "package '{pkg}' does not have a config definition"
+    )?;
+    let path = hmgr::paths::config_file(pkg.to_str());
+    let changed = cu::check!(
+        config_def.reset(&path, key),
+        "failed to reset config for '{pkg}'"
+    )?;
+    if !changed {
+        cu::info!("no change");
+        return Ok(());
+    }
+    match key {
+        Some(key) => {
+            mark_dirty(pkg, &format!("reset config {key}"))?;
+            cu::info!("reset '{key}' for '{pkg}'");
+        }
+        None => {
+            mark_dirty(pkg, "reset config")?;
+            cu::info!("reset config for '{pkg}'");
+        }
+    }
+    cu::hint!(r"after done configuring, please run `shaft sync`");
+    Ok(())
+}
+
+pub fn config_dirty(package: &str) -> cu::Result<()> {
+    let pkg = cu::check!(PkgId::from_str(package), "cannot find package '{package}'")?;
+    mark_dirty(pkg, "dirty config")?;
     cu::info!("dirtied '{package}'");
     Ok(())
 }
@@ -54,6 +202,7 @@ pub fn config_dirty_all() -> cu::Result<()> {
     let mut installed = InstallCache::load()?;
     for pkg in installed.pkgs {
         installed.set_dirty(pkg, true);
+        crate::history::record_package(pkg, "dirty config", None);
         cu::info!("dirtied '{pkg}'");
     }
     cu::check!(installed.save(), "failed to save configuration")?;
@@ -0,0 +1,339 @@
+use std::path::Path;
+
+use corelib::{ItemMgr, hmgr};
+use cu::pre::*;
+use enumset::EnumSet;
+use registry::{Context, PkgId, Stage};
+
+use crate::graph::InstallCache;
+
+/// Check the environment this tool depends on, and print a fix for each problem found
+pub fn doctor() -> cu::Result<()> {
+    cu::lv::disable_print_time();
+    let mut doctor = Doctor::default();
+    if doctor.check("SHAFT_HOME", check_home) {
+        doctor.check("PATH", check_path);
+        doctor.check("init scripts", check_init_scripts);
+        doctor.check("lock file", check_lock);
+        doctor.check("environment assertions", check_env_asserts);
+        doctor.check("binaries", check_binaries);
+    }
+    match doctor.problems {
+        0 => {
+            cu::info!("no problems found");
+            Ok(())
+        }
+        1 => cu::bail!("found 1 problem"),
+        x => cu::bail!("found {x} problems"),
+    }
+}
+
+#[derive(Default)]
+struct Doctor {
+    problems: usize,
+}
+
+impl Doctor {
+    /// Run a check, returns if the check passed without error
+    fn check(&mut self, name: &str, f: impl FnOnce(&mut Self) -> cu::Result<bool>) -> bool {
+        cu::debug!("doctor: checking {name}");
+        match f(self) {
+            Ok(x) => x,
+            Err(e) => {
+                cu::error!("{e:?}");
+                self.problem(
+                    format!("failed to check {name}"),
+                    "rerun with -vv to see more details",
+                );
+                false
+            }
+        }
+    }
+
+    fn problem(&mut self, problem: impl std::fmt::Display, fix: impl std::fmt::Display) {
+        self.problems += 1;
+        cu::error!("{problem}");
+        cu::hint!("fix: {fix}");
+    }
+}
+
+fn check_home(doctor: &mut Doctor) -> cu::Result<bool> {
+    let home_path_str = cu::env_var("SHAFT_HOME").unwrap_or_default();
+    if home_path_str.is_empty() {
+        doctor.problem(
+            "SHAFT_HOME is not set",
+            "if the tool is initialized, make sure the init scripts are sourced by the shell profile and restart the session; otherwise, run `shaft` to initialize",
+        );
+        return Ok(false);
+    }
+    let home_path = Path::new(&home_path_str);
+    if !home_path.is_dir() {
+        doctor.problem(
+            format!("SHAFT_HOME does not point to an existing directory: '{home_path_str}'"),
+            "run `shaft` to create the home directory, or correct SHAFT_HOME in the shell profile",
+        );
+        return Ok(false);
+    }
+    let home_path = home_path.normalize()?;
+    cu::info!("SHAFT_HOME: '{}'", home_path.display());
+    hmgr::paths::init_home_path(home_path);
+    Ok(true)
+}
+
+fn check_path(doctor: &mut Doctor) -> cu::Result<bool> {
+    let (separator, system_dirs) = if cfg!(windows) {
+        let mut system_root = cu::env_var("SystemRoot")?;
+        system_root.push_str("\\system32");
+        (';', vec![normalize_path_entry(&system_root)])
+    } else {
+        let system_dirs = [
+            "/usr/local/bin",
+            "/usr/bin",
+            "/bin",
+            "/usr/sbin",
+            "/sbin",
+            "/opt/homebrew/bin",
+        ];
+        (
+            ':',
+            system_dirs.into_iter().map(normalize_path_entry).collect(),
+        )
+    };
+    let paths = cu::env_var("PATH")?;
+    let paths = paths
+        .split(separator)
+        .map(normalize_path_entry)
+        .collect::<Vec<_>>();
+    let first_system_dir = paths.iter().position(|x| system_dirs.contains(x));
+
+    // on windows, HOME/bin is in the user PATH, which always comes after the system PATH,
+    // and only HOME/sbin (in the system PATH) needs to come before system32
+    let bin_root = hmgr::paths::bin_root();
+    let sbin_root = hmgr::paths::sbin_root();
+    let mut dirs = vec![(bin_root, cfg!(not(windows)))];
+    if cfg!(windows) {
+        dirs.push((sbin_root, true));
+    }
+
+    let mut ok = true;
+    for (dir, before_system) in dirs {
+        let dir_str = dir.as_utf8()?;
+        let Some(i) = paths
+            .iter()
+            .position(|x| *x == normalize_path_entry(dir_str))
+        else {
+            ok = false;
+            if cfg!(windows) {
+                doctor.problem(
+                    format!("'{dir_str}' is not in PATH"),
+                    "restart all terminal processes; if it persists, run `shaft sync core` to rebuild the PATH",
+                );
+            } else {
+                doctor.problem(
+                    format!("'{dir_str}' is not in PATH"),
+                    "make sure the init scripts are sourced by the shell profile, then restart the session",
+                );
+            }
+            continue;
+        };
+        if !before_system {
+            continue;
+        }
+        let Some(j) = first_system_dir else {
+            continue;
+        };
+        if j < i {
+            ok = false;
+            if cfg!(windows) {
+                doctor.problem(
+                    format!("'{dir_str}' comes after '{}' in PATH", paths[j]),
+                    format!(
+                        "edit the SYSTEM PATH to put '{dir_str}' before '{}'",
+                        paths[j]
+                    ),
+                );
+            } else {
+                doctor.problem(
+                    format!("'{dir_str}' comes after '{}' in PATH", paths[j]),
+                    "source the init scripts at the end of the shell profile, after anything else that modifies PATH",
+                );
+            }
+        }
+    }
+    Ok(ok)
+}
+
+fn normalize_path_entry(p: &str) -> String {
+    let p = p.trim().trim_end_matches(['/', '\\']);
+    if cfg!(windows) {
+        p.to_ascii_lowercase()
+    } else {
+        p.to_string()
+    }
+}
+
+fn check_init_scripts(doctor: &mut Doctor) -> cu::Result<bool> {
+    let Some(user_home) = std::env::home_dir() else {
+        cu::bail!("failed to get user home");
+    };
+    // this is best effort - the profile could source the scripts indirectly
+    let scripts = if cfg!(windows) {
+        vec![(
+            hmgr::paths::init_ps1(),
+            vec![
+                "Documents\\PowerShell\\profile.ps1",
+                "Documents\\PowerShell\\Microsoft.PowerShell_profile.ps1",
+                "Documents\\WindowsPowerShell\\profile.ps1",
+                "Documents\\WindowsPowerShell\\Microsoft.PowerShell_profile.ps1",
+            ],
+        )]
+    } else if cu::env_var("SHELL").unwrap_or_default().ends_with("zsh") {
+        vec![
+            (hmgr::paths::init_zsh(), vec![".zshrc"]),
+            (
+                hmgr::paths::init_profile_zsh(),
+                vec![".zprofile", ".zshenv"],
+            ),
+        ]
+    } else {
+        vec![
+            (hmgr::paths::init_bash(), vec![".bashrc"]),
+            (
+                hmgr::paths::init_profile_bash(),
+                vec![".bash_profile", ".bash_login", ".profile"],
+            ),
+        ]
+    };
+
+    let mut ok = true;
+    for (script, profiles) in scripts {
+        let script_name = script
+            .file_name()
+            .map(|x| x.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let is_sourced = profiles.iter().any(|profile| {
+            cu::fs::read_string(user_home.join(profile))
+                .map(|content| content.contains(&script_name))
+                .unwrap_or_default()
+        });
+        if is_sourced {
+            continue;
+        }
+        ok = false;
+        let profile = user_home.join(profiles[0]);
+        doctor.problem(
+            format!(
+                "'{script_name}' is not sourced by the shell profile '{}'",
+                profile.display()
+            ),
+            format!(
+                "add `. {}` to '{}', then restart the session",
+                script.display(),
+                profile.display()
+            ),
+        );
+    }
+    Ok(ok)
+}
+
+fn check_lock(doctor: &mut Doctor) -> cu::Result<bool> {
+    if !hmgr::has_stale_lock()? {
+        return Ok(true);
+    }
+    let path = hmgr::paths::dot_lock();
+    doctor.problem(
+        format!("stale lock file: '{}'", path.display()),
+        "the program may have crashed earlier; make sure no other instance is running, then delete the lock file",
+    );
+    Ok(false)
+}
+
+fn check_env_asserts(doctor: &mut Doctor) -> cu::Result<bool> {
+    let mut ok = true;
+    for (key, expected) in hmgr::env_asserts()? {
+        let actual = cu::env_var(&key).unwrap_or_default();
+        if actual == expected {
+            continue;
+        }
+        ok = false;
+        let fix = if cfg!(windows) {
+            "restart all terminal processes to refresh the environment"
+        } else {
+            "restart the session to refresh the environment; if it persists, make sure the init scripts are sourced by the shell profile"
+        };
+        doctor.problem(
+            format!("env var '{key}' is '{actual}', but expected '{expected}'"),
+            fix,
+        );
+    }
+    Ok(ok)
+}
+
+fn check_binaries(doctor: &mut Doctor) -> cu::Result<bool> {
+    let installed = InstallCache::load()?;
+    let mut ctx = Context::new(ItemMgr::default());
+    for pkg in installed.pkgs {
+        ctx.set_installed(pkg, true);
+    }
+    let bin_root = hmgr::paths::bin_root();
+    let sbin_root = hmgr::paths::sbin_root();
+
+    let mut ok = true;
+    for pkg in EnumSet::<PkgId>::all() {
+        let package = pkg.package();
+        if !package.enabled() {
+            continue;
+        }
+        let outside_bins = package
+            .binaries()
+            .iter()
+            .filter_map(|bin| {
+                // provided by another installed package, such as system-*
+                if installed.bins[bin].is_some_and(|x| x != pkg) {
+                    return None;
+                }
+                let path = cu::which(bin.to_str()).ok()?;
+                if path.starts_with(&bin_root) || path.starts_with(&sbin_root) {
+                    return None;
+                }
+                Some((bin, path))
+            })
+            .collect::<Vec<_>>();
+        if outside_bins.is_empty() {
+            continue;
+        }
+        // binaries outside of shaft are only a problem if the package
+        // refuses to be managed because of it
+        ctx.pkg = pkg;
+        ctx.stage.set(Stage::Verify);
+        let Err(e) = package.verify(&ctx) else {
+            continue;
+        };
+        cu::debug!("failed to verify '{pkg}': {e:?}");
+        ok = false;
+        for (bin, path) in outside_bins {
+            let mut fix = format!(
+                "uninstall the existing '{bin}', or make sure '{}' comes before '{}' in PATH",
+                bin_root.display(),
+                path.parent().unwrap_or(&path).display()
+            );
+            if let Some(system_pkg) = bin
+                .providers()
+                .iter()
+                .find(|x| x.package().enabled() && x.to_str().starts_with("system-"))
+            {
+                fix.push_str(&format!(
+                    "; or, to use the existing installation, run `shaft sync {system_pkg}`"
+                ));
+            }
+            doctor.problem(
+                format!(
+                    "'{bin}' (provided by '{pkg}') is installed outside of shaft at '{}'",
+                    path.display()
+                ),
+                fix,
+            );
+        }
+    }
+    Ok(ok)
+}
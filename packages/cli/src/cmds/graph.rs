@@ -0,0 +1,258 @@
+use std::fmt::Write as _;
+
+use cu::pre::*;
+use enumset::EnumSet;
+use registry::{BinId, PkgId};
+
+use crate::graph::InstallCache;
+
+/// Output format of the dependency graph
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// Graphviz DOT
+    Dot,
+    /// Mermaid flowchart
+    Mermaid,
+    /// JSON object with the nodes and edges
+    Json,
+}
+
+/// Print the dependency graph of the packages, including their dependencies.
+/// If no packages are specified, print the graph of the whole registry
+pub fn graph(packages: &[String], format: GraphFormat) -> cu::Result<()> {
+    cu::lv::disable_print_time();
+    let pkgs = crate::graph::parse_pkgs(packages)?;
+    let pkgs = if pkgs.is_empty() {
+        EnumSet::all()
+    } else {
+        with_dependencies(pkgs)
+    };
+    let installed = InstallCache::load()?;
+
+    let mut edges = vec![];
+    for pkg in pkgs {
+        let package = pkg.package();
+        for bin in package.binary_dependencies() {
+            for provider in bin.providers() {
+                edges.push((pkg, provider, Some(bin)));
+            }
+        }
+        for cfg in package.config_dependencies() {
+            edges.push((pkg, cfg, None));
+        }
+    }
+
+    let out = match format {
+        GraphFormat::Dot => emit_dot(pkgs, &edges, &installed),
+        GraphFormat::Mermaid => emit_mermaid(pkgs, &edges, &installed),
+        GraphFormat::Json => emit_json(pkgs, &edges, &installed)?,
+    };
+    println!("{out}");
+    Ok(())
+}
+
+/// Edge from a package to its dependency, with the binary if it's a binary dependency,
+/// or `None` if it's a config dependency
+type Edge = (PkgId, PkgId, Option<BinId>);
+
+/// Add all packages the packages may depend on
+fn with_dependencies(mut pkgs: EnumSet<PkgId>) -> EnumSet<PkgId> {
+    loop {
+        let len_before = pkgs.len();
+        for pkg in pkgs {
+            let package = pkg.package();
+            for bin in package.binary_dependencies() {
+                pkgs.insert_all(bin.providers());
+            }
+            pkgs.insert_all(package.config_dependencies());
+        }
+        if pkgs.len() == len_before {
+            break;
+        }
+    }
+    pkgs
+}
+
+fn emit_dot(pkgs: EnumSet<PkgId>, edges: &[Edge], installed: &InstallCache) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "digraph shaft {{");
+    let _ = writeln!(out, "  rankdir=LR;");
+    let _ = writeln!(out, "  node [shape=box];");
+    for pkg in pkgs {
+        let mut attrs = vec![];
+        if installed.pkgs.contains(pkg) {
+            attrs.push(r##"style=filled, fillcolor="#c6efce""##);
+        }
+        if !pkg.package().enabled() {
+            attrs.push("color=gray, fontcolor=gray");
+        }
+        if attrs.is_empty() {
+            let _ = writeln!(out, "  \"{pkg}\";");
+        } else {
+            let _ = writeln!(out, "  \"{pkg}\" [{}];", attrs.join(", "));
+        }
+    }
+    for (from, to, bin) in edges {
+        match bin {
+            Some(bin) => {
+                let _ = writeln!(out, "  \"{from}\" -> \"{to}\" [label=\"{bin}\"];");
+            }
+            None => {
+                let _ = writeln!(
+                    out,
+                    "  \"{from}\" -> \"{to}\" [label=\"config\", style=dashed];"
+                );
+            }
+        }
+    }
+    let _ = write!(out, "}}");
+    out
+}
+
+fn emit_mermaid(pkgs: EnumSet<PkgId>, edges: &[Edge], installed: &InstallCache) -> String {
+    // mermaid IDs don't play well with '-'
+    fn id(pkg: PkgId) -> String {
+        pkg.to_str().replace('-', "_")
+    }
+    let mut out = String::new();
+    let _ = writeln!(out, "flowchart LR");
+    for pkg in pkgs {
+        let _ = write!(out, "  {}[\"{pkg}\"]", id(pkg));
+        if installed.pkgs.contains(pkg) {
+            let _ = write!(out, ":::installed");
+        } else if !pkg.package().enabled() {
+            let _ = write!(out, ":::disabled");
+        }
+        let _ = writeln!(out);
+    }
+    for (from, to, bin) in edges {
+        match bin {
+            Some(bin) => {
+                let _ = writeln!(out, "  {} -->|{bin}| {}", id(*from), id(*to));
+            }
+            None => {
+                let _ = writeln!(out, "  {} -.->|config| {}", id(*from), id(*to));
+            }
+        }
+    }
+    let _ = writeln!(out, "  classDef installed fill:#c6efce");
+    let _ = write!(out, "  classDef disabled color:gray,stroke-dasharray:4");
+    out
+}
+
+fn emit_json(pkgs: EnumSet<PkgId>, edges: &[Edge], installed: &InstallCache) -> cu::Result<String> {
+    let nodes = pkgs
+        .iter()
+        .map(|pkg| GraphNodeJson {
+            name: pkg.to_str(),
+            enabled: pkg.package().enabled(),
+            installed: installed.pkgs.contains(pkg),
+        })
+        .collect();
+    let edges = edges
+        .iter()
+        .map(|(from, to, bin)| GraphEdgeJson {
+            from: from.to_str(),
+            to: to.to_str(),
+            binary: bin.map(|x| x.to_str()),
+        })
+        .collect();
+    let graph = GraphJson { nodes, edges };
+    json::stringify(&graph)
+}
+
+#[derive(Serialize)]
+struct GraphJson {
+    nodes: Vec<GraphNodeJson>,
+    edges: Vec<GraphEdgeJson>,
+}
+
+#[derive(Serialize)]
+struct GraphNodeJson {
+    name: &'static str,
+    /// If the package is enabled on the current platform
+    enabled: bool,
+    installed: bool,
+}
+
+#[derive(Serialize)]
+struct GraphEdgeJson {
+    /// The dependent package
+    from: &'static str,
+    /// The package depended on
+    to: &'static str,
+    /// The binary if it's a binary dependency, `None` if it's a config dependency
+    binary: Option<&'static str>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn fixture() -> (EnumSet<PkgId>, Vec<Edge>, InstallCache) {
+        let pkgs = PkgId::Python | PkgId::CargoBinstall | PkgId::SystemGit;
+        let edges = vec![
+            (
+                PkgId::Python,
+                PkgId::CargoBinstall,
+                Some(BinId::CargoBinstall),
+            ),
+            (PkgId::Python, PkgId::SystemGit, None),
+        ];
+        let installed = InstallCache {
+            pkgs: PkgId::CargoBinstall.into(),
+            ..Default::default()
+        };
+        (pkgs, edges, installed)
+    }
+
+    #[test]
+    fn test_emit_dot() {
+        let (pkgs, edges, installed) = fixture();
+        let expected = r##"digraph shaft {
+  rankdir=LR;
+  node [shape=box];
+  "cargo-binstall" [style=filled, fillcolor="#c6efce"];
+  "python";
+  "system-git";
+  "python" -> "cargo-binstall" [label="cargo-binstall"];
+  "python" -> "system-git" [label="config", style=dashed];
+}"##;
+        assert_eq!(emit_dot(pkgs, &edges, &installed), expected);
+    }
+
+    #[test]
+    fn test_emit_mermaid() {
+        let (pkgs, edges, installed) = fixture();
+        let expected = r##"flowchart LR
+  cargo_binstall["cargo-binstall"]:::installed
+  python["python"]
+  system_git["system-git"]
+  python -->|cargo-binstall| cargo_binstall
+  python -.->|config| system_git
+  classDef installed fill:#c6efce
+  classDef disabled color:gray,stroke-dasharray:4"##;
+        assert_eq!(emit_mermaid(pkgs, &edges, &installed), expected);
+    }
+
+    #[test]
+    fn test_emit_json() -> cu::Result<()> {
+        let (pkgs, edges, installed) = fixture();
+        let value = json::parse::<json::Value>(&emit_json(pkgs, &edges, &installed)?)?;
+        let expected = json::parse::<json::Value>(
+            r#"{
+                "nodes": [
+                    { "name": "cargo-binstall", "enabled": true, "installed": true },
+                    { "name": "python", "enabled": true, "installed": false },
+                    { "name": "system-git", "enabled": true, "installed": false }
+                ],
+                "edges": [
+                    { "from": "python", "to": "cargo-binstall", "binary": "cargo-binstall" },
+                    { "from": "python", "to": "system-git", "binary": null }
+                ]
+            }"#,
+        )?;
+        assert_eq!(value, expected);
+        Ok(())
+    }
+}
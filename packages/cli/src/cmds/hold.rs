@@ -0,0 +1,36 @@
+use crate::graph::{self, InstallCache};
+
+/// Hold the packages at the installed version, or release the hold.
+///
+/// Held packages are not upgraded when syncing all installed packages,
+/// but are still re-configured when needed
+pub fn hold(packages: &[String], hold: bool) -> cu::Result<()> {
+    let pkgs = graph::parse_pkgs(packages)?;
+    if pkgs.is_empty() {
+        cu::bail!("please specify packages to hold or unhold");
+    }
+    let mut installed = InstallCache::load()?;
+    for pkg in pkgs {
+        if !installed.pkgs.contains(pkg) {
+            cu::warn!("'{pkg}' is not installed, skipping");
+            continue;
+        }
+        if installed.is_held(pkg) == hold {
+            if hold {
+                cu::info!("'{pkg}' is already held");
+            } else {
+                cu::info!("'{pkg}' is not held");
+            }
+            continue;
+        }
+        installed.set_held(pkg, hold);
+        if hold {
+            crate::history::record_package(pkg, "hold", None);
+            cu::info!("held '{pkg}'");
+        } else {
+            crate::history::record_package(pkg, "unhold", None);
+            cu::info!("released hold on '{pkg}'");
+        }
+    }
+    installed.save()
+}
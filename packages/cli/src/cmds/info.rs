@@ -1,10 +1,25 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
 use corelib::ItemMgr;
+use cu::pre::*;
 use enumset::EnumSet;
 use itertools::Itertools as _;
-use registry::{BinId, Context, PkgId};
+use registry::{BinId, Context, GroupId, PkgId};
 
 use crate::graph::InstallCache;
 
+/// How to print the query results
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InfoOutput {
+    /// Human-readable output
+    Pretty,
+    /// Only print the name of the package, one per line
+    Machine,
+    /// Print structured records of the packages as a JSON array
+    Json,
+}
+
 /// Query for package information
 pub fn info(
     query: &str,
@@ -12,7 +27,7 @@ pub fn info(
     installed_only: bool,
     binary_only: bool,
     package_only: bool,
-    machine_mode: bool,
+    output: InfoOutput,
 ) -> cu::Result<bool> {
     let query = query.trim();
     cu::debug!("querying for '{query}'");
@@ -21,24 +36,21 @@ pub fn info(
     }
 
     if query.is_empty() {
-        return show_results(
-            None,
-            EnumSet::all(),
-            installed_only,
-            false,
-            None,
-            machine_mode,
-        );
+        return show_results(None, EnumSet::all(), installed_only, false, None, output);
+    }
+
+    if let Some(group) = query.strip_prefix('@') {
+        let pkgs = crate::graph::parse_group(group)?;
+        if output == InfoOutput::Pretty
+            && let Some(group) = GroupId::from_str(group)
+        {
+            cu::print!("@{group}: {}", group.description());
+        }
+        return show_results(None, pkgs, installed_only, false, None, output);
     }
 
     if !search {
-        return info_exact(
-            query,
-            installed_only,
-            binary_only,
-            package_only,
-            machine_mode,
-        );
+        return info_exact(query, installed_only, binary_only, package_only, output);
     }
 
     let query_mode = QueryMode::parse(query)?;
@@ -52,7 +64,7 @@ pub fn info(
             }
             pkgs.insert(pkg);
         }
-        return show_results(exact_pkg, pkgs, installed_only, false, None, machine_mode);
+        return show_results(exact_pkg, pkgs, installed_only, false, None, output);
     }
 
     if binary_only {
@@ -66,14 +78,7 @@ pub fn info(
             }
             pkgs.extend(bin.providers());
         }
-        return show_results(
-            exact_pkg,
-            pkgs,
-            installed_only,
-            true,
-            exact_bin,
-            machine_mode,
-        );
+        return show_results(exact_pkg, pkgs, installed_only, true, exact_bin, output);
     }
 
     let exact_pkg = PkgId::from_str(query);
@@ -94,14 +99,7 @@ pub fn info(
     if let Some(pkg) = exact_pkg {
         pkgs.remove(pkg);
     }
-    show_results(
-        exact_pkg,
-        pkgs,
-        installed_only,
-        false,
-        exact_bin,
-        machine_mode,
-    )
+    show_results(exact_pkg, pkgs, installed_only, false, exact_bin, output)
 }
 
 fn info_exact(
@@ -109,7 +107,7 @@ fn info_exact(
     installed_only: bool,
     binary_only: bool,
     package_only: bool,
-    machine_mode: bool,
+    output: InfoOutput,
 ) -> cu::Result<bool> {
     let the_pkg = PkgId::from_str(query_exact);
     let the_bin = BinId::from_str(query_exact);
@@ -125,7 +123,7 @@ fn info_exact(
             return Ok(false);
         };
         let installed = InstallCache::load()?;
-        display_pkg_detail(&installed, the_pkg, machine_mode);
+        display_pkg_detail(&installed, the_pkg, output);
         return Ok(true);
     }
 
@@ -158,18 +156,11 @@ fn info_exact(
             }
             _ => None,
         };
-        return show_results(
-            exact_pkg,
-            pkgs,
-            installed_only,
-            true,
-            Some(the_bin),
-            machine_mode,
-        );
+        return show_results(exact_pkg, pkgs, installed_only, true, Some(the_bin), output);
     }
 
     let pkgs = the_bin.map(|x| x.providers()).unwrap_or_default();
-    let result = show_results(the_pkg, pkgs, installed_only, false, the_bin, machine_mode)?;
+    let result = show_results(the_pkg, pkgs, installed_only, false, the_bin, output)?;
     if !result {
         cu::hint!("no results found, try search mode: `shaft info -s {query_exact}`");
     }
@@ -182,7 +173,7 @@ fn show_results(
     installed_only: bool,
     binary_only: bool,
     binary_result: Option<BinId>,
-    machine_mode: bool,
+    output: InfoOutput,
 ) -> cu::Result<bool> {
     let installed = InstallCache::load()?;
     if let Some(pkg) = exact_pkg {
@@ -200,6 +191,11 @@ fn show_results(
             }
         }
     }
+    if output == InfoOutput::Json {
+        // no matches is an empty array, not an error
+        display_pkgs_json(&installed, exact_pkg.into_iter().chain(rest_pkgs));
+        return Ok(true);
+    }
     match (exact_pkg, rest_pkgs.len()) {
         (None, 0) => {
             return Ok(false);
@@ -208,7 +204,7 @@ fn show_results(
             if let Some(bin) = binary_result {
                 cu::info!("found 1 provider for the binary '{bin}'");
             }
-            display_pkg_detail(&installed, rest_pkgs.iter().next().unwrap(), machine_mode);
+            display_pkg_detail(&installed, rest_pkgs.iter().next().unwrap(), output);
         }
         (None, x) => {
             if let Some(bin) = binary_result {
@@ -216,39 +212,46 @@ fn show_results(
             } else {
                 cu::info!("found {x} packages");
             }
-            display_pkgs_summary(&installed, rest_pkgs, machine_mode);
+            display_pkgs_summary(&installed, rest_pkgs, output);
         }
         (Some(pkg), 0) => {
-            display_pkg_detail(&installed, pkg, machine_mode);
+            display_pkg_detail(&installed, pkg, output);
         }
         (Some(pkg), x) => {
             let len = x + 1;
             if let Some(bin) = binary_result {
                 if binary_only {
                     cu::info!("found {len} providers for the binary '{bin}'");
-                    display_pkg_detail(&installed, pkg, machine_mode);
+                    display_pkg_detail(&installed, pkg, output);
                     cu::info!("the following packages also provide the binary:");
-                    display_pkgs_summary(&installed, rest_pkgs, machine_mode);
+                    display_pkgs_summary(&installed, rest_pkgs, output);
                 } else {
-                    display_pkg_detail(&installed, pkg, machine_mode);
+                    display_pkg_detail(&installed, pkg, output);
                     cu::info!("other packages that matched:");
-                    display_pkgs_summary(&installed, rest_pkgs, machine_mode);
+                    display_pkgs_summary(&installed, rest_pkgs, output);
                 }
             } else {
                 cu::info!("found {len} packages");
-                display_pkg_detail(&installed, pkg, machine_mode);
+                display_pkg_detail(&installed, pkg, output);
                 cu::info!("other packages that matched:");
-                display_pkgs_summary(&installed, rest_pkgs, machine_mode);
+                display_pkgs_summary(&installed, rest_pkgs, output);
             }
         }
     }
     Ok(true)
 }
 
-fn display_pkg_detail(installed: &InstallCache, pkg: PkgId, machine_mode: bool) {
-    if machine_mode {
-        println!("{pkg}");
-        return;
+fn display_pkg_detail(installed: &InstallCache, pkg: PkgId, output: InfoOutput) {
+    match output {
+        InfoOutput::Pretty => {}
+        InfoOutput::Machine => {
+            println!("{pkg}");
+            return;
+        }
+        InfoOutput::Json => {
+            display_pkgs_json(installed, [pkg]);
+            return;
+        }
     }
     let package = pkg.package();
     if !package.enabled() {
@@ -291,19 +294,34 @@ fn display_pkg_detail(installed: &InstallCache, pkg: PkgId, machine_mode: bool)
         .join(", ");
     cu::print!("      bin_deps: [{bin_deps}]");
     let cfg_deps = package
-        .binary_dependencies()
+        .config_dependencies()
         .iter()
         .map(|x| x.to_str())
         .join(", ");
     cu::print!("      cfg_deps: [{cfg_deps}]");
+    let opt_deps = package
+        .optional_dependencies()
+        .iter()
+        .map(|x| x.to_str())
+        .join(", ");
+    cu::print!("      opt_deps: [{opt_deps}]");
+    let conflicts = package.conflicts().iter().map(|x| x.to_str()).join(", ");
+    cu::print!("     conflicts: [{conflicts}]");
     cu::print!("");
 }
-fn display_pkgs_summary(installed: &InstallCache, pkgs: EnumSet<PkgId>, machine_mode: bool) {
-    if machine_mode {
-        for pkg in pkgs {
-            println!("{pkg}");
+fn display_pkgs_summary(installed: &InstallCache, pkgs: EnumSet<PkgId>, output: InfoOutput) {
+    match output {
+        InfoOutput::Pretty => {}
+        InfoOutput::Machine => {
+            for pkg in pkgs {
+                println!("{pkg}");
+            }
+            return;
+        }
+        InfoOutput::Json => {
+            display_pkgs_json(installed, pkgs);
+            return;
         }
-        return;
     }
     let package_field_width = pkgs
         .iter()
@@ -328,6 +346,77 @@ fn display_pkgs_summary(installed: &InstallCache, pkgs: EnumSet<PkgId>, machine_
     }
 }
 
+fn display_pkgs_json(installed: &InstallCache, pkgs: impl IntoIterator<Item = PkgId>) {
+    let mut ctx = Context::new(ItemMgr::default());
+    let records = pkgs
+        .into_iter()
+        .map(|pkg| {
+            let package = pkg.package();
+            ctx.pkg = pkg;
+            let config_location = match package.config_location(&ctx) {
+                Ok(x) => x,
+                Err(e) => {
+                    cu::warn!("failed to get config location for '{pkg}': {e:?}");
+                    None
+                }
+            };
+            PkgInfoJson {
+                name: pkg.to_str(),
+                short_desc: package.short_desc,
+                long_desc: package.long_desc,
+                enabled: package.enabled(),
+                installed: installed.pkgs.contains(pkg),
+                dirty: installed.is_dirty(pkg),
+                binaries: package
+                    .binaries()
+                    .iter()
+                    .map(|bin| (bin.to_str(), installed.bins[bin].map(|x| x.to_str())))
+                    .collect(),
+                binary_dependencies: package
+                    .binary_dependencies()
+                    .iter()
+                    .map(|x| x.to_str())
+                    .collect(),
+                config_dependencies: package
+                    .config_dependencies()
+                    .iter()
+                    .map(|x| x.to_str())
+                    .collect(),
+                optional_dependencies: package
+                    .optional_dependencies()
+                    .iter()
+                    .map(|x| x.to_str())
+                    .collect(),
+                conflicts: package.conflicts().iter().map(|x| x.to_str()).collect(),
+                config_location,
+            }
+        })
+        .collect::<Vec<_>>();
+    match json::stringify(&records) {
+        Ok(x) => println!("{x}"),
+        Err(e) => cu::error!("failed to serialize package info: {e:?}"),
+    }
+}
+
+#[derive(Serialize)]
+struct PkgInfoJson {
+    name: &'static str,
+    short_desc: &'static str,
+    long_desc: &'static str,
+    /// If the package is enabled on the current platform
+    enabled: bool,
+    installed: bool,
+    dirty: bool,
+    /// Binaries provided by the package, mapping to the installed package
+    /// that currently provides it
+    binaries: BTreeMap<&'static str, Option<&'static str>>,
+    binary_dependencies: Vec<&'static str>,
+    config_dependencies: Vec<&'static str>,
+    optional_dependencies: Vec<&'static str>,
+    conflicts: Vec<&'static str>,
+    config_location: Option<PathBuf>,
+}
+
 fn display_bool(x: bool) -> &'static str {
     if x { "yes" } else { "no" }
 }
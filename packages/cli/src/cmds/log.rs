@@ -0,0 +1,61 @@
+use cu::pre::*;
+use registry::PkgId;
+
+use crate::history;
+
+/// Print the history of operations, optionally only the ones that touched the package
+pub fn log(package: Option<&str>) -> cu::Result<()> {
+    cu::lv::disable_print_time();
+    let pkg = match package {
+        Some(package) => Some(cu::check!(
+            PkgId::from_str(package),
+            "cannot find package '{package}'"
+        )?),
+        None => None,
+    };
+    let entries = history::load()?;
+    let mut count = 0;
+    for entry in entries {
+        if let Some(pkg) = pkg
+            && !entry.packages.iter().any(|x| x.name == pkg.to_str())
+        {
+            continue;
+        }
+        count += 1;
+        let time = history::format_timestamp(entry.timestamp);
+        let commit = match &entry.commit {
+            Some(x) => &x[..x.len().min(8)],
+            None => "(none)",
+        };
+        let status = if entry.error.is_some() {
+            "FAILED"
+        } else {
+            "ok"
+        };
+        cu::hint!(
+            "{time} UTC | {status:>6} | {commit} | shaft {}",
+            entry.args.join(" ")
+        );
+        for package in &entry.packages {
+            if pkg.is_some_and(|x| x.to_str() != package.name) {
+                continue;
+            }
+            let before = package.before.as_deref().unwrap_or("?");
+            let after = package.after.as_deref().unwrap_or("?");
+            cu::print!(
+                "  {}: {} ({before} -> {after})",
+                package.name,
+                package.action
+            );
+        }
+        if let Some(error) = &entry.error {
+            for line in error.lines() {
+                cu::print!("  {line}");
+            }
+        }
+    }
+    if count == 0 {
+        cu::info!("no history found");
+    }
+    Ok(())
+}
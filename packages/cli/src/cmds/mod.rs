@@ -1,10 +1,33 @@
 mod sync;
-pub use sync::{sync, sync_pkgs};
+pub use sync::{sync, sync_pkgs, sync_pkgs_with_providers};
 mod remove;
 pub use remove::remove;
+mod autoremove;
+pub use autoremove::autoremove;
+mod rollback;
+pub use rollback::rollback;
+mod hold;
+pub use hold::hold;
 mod config;
-pub use config::{config, config_dirty, config_dirty_all, config_location};
+pub use config::{
+    config, config_diff, config_dirty, config_dirty_all, config_get, config_location, config_reset,
+    config_set,
+};
 mod info;
-pub use info::info;
+pub use info::{InfoOutput, info};
 mod clean;
 pub use clean::clean;
+mod status;
+pub use status::status;
+mod outdated;
+pub use outdated::outdated;
+mod doctor;
+pub use doctor::doctor;
+mod why;
+pub use why::why;
+mod graph;
+pub use graph::{GraphFormat, graph};
+mod apply;
+pub use apply::apply;
+mod log;
+pub use log::log;
@@ -0,0 +1,67 @@
+use corelib::ItemMgr;
+use registry::{Context, Stage, Verified};
+
+use crate::graph::InstallCache;
+
+/// Print the installed and expected versions of the installed packages
+/// that will be upgraded by `sync`. With `all`, print all installed packages
+pub fn outdated(all: bool) -> cu::Result<()> {
+    cu::lv::disable_print_time();
+    let installed = InstallCache::load()?;
+    let mut ctx = Context::new(ItemMgr::default());
+    for pkg in installed.pkgs {
+        ctx.set_installed(pkg, true);
+    }
+
+    let mut rows = Vec::with_capacity(installed.pkgs.len());
+    for pkg in installed.pkgs {
+        ctx.pkg = pkg;
+        ctx.stage.set(Stage::Verify);
+        let (status, version) = match pkg.package().verify(&ctx) {
+            Ok(Verified::NotUpToDate(version)) => {
+                if installed.is_rolled_back(pkg) {
+                    ("rolled back".to_string(), version)
+                } else if installed.is_held(pkg) {
+                    ("held".to_string(), version)
+                } else {
+                    ("outdated".to_string(), version)
+                }
+            }
+            Ok(_) if !all => continue,
+            Ok(Verified::UpToDate(version)) => ("up to date".to_string(), version),
+            Ok(Verified::NeedsConfig) => ("needs-config".to_string(), None),
+            Ok(Verified::NotInstalled) => ("missing".to_string(), None),
+            Err(e) => (format!("error: {e}"), None),
+        };
+        let (current, expected) = match version {
+            Some(x) => (x.current, x.expected),
+            None => ("-".to_string(), "-".to_string()),
+        };
+        rows.push((pkg, current, expected, status));
+    }
+    if rows.is_empty() {
+        cu::info!("all installed packages are up to date");
+        return Ok(());
+    }
+
+    let package_width = rows
+        .iter()
+        .map(|x| x.0.to_str().len())
+        .max()
+        .unwrap_or(10)
+        .max(10);
+    let current_width = rows.iter().map(|x| x.1.len()).max().unwrap_or(7).max(7);
+    let expected_width = rows.iter().map(|x| x.2.len()).max().unwrap_or(8).max(8);
+    cu::hint!(
+        "{:>package_width$} | {:>current_width$} | {:>expected_width$} | status\n------------------------------------------------------------",
+        "package",
+        "current",
+        "expected"
+    );
+    for (pkg, current, expected, status) in rows {
+        cu::print!(
+            "{pkg:>package_width$} | {current:>current_width$} | {expected:>expected_width$} | {status}"
+        );
+    }
+    Ok(())
+}
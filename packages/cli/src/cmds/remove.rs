@@ -1,17 +1,31 @@
 use corelib::ItemMgr;
 use cu::pre::*;
 use enumset::EnumSet;
+use itertools::Itertools as _;
 use registry::{Context, PkgId, Stage, Verified};
 
 use crate::graph::{self, InstallCache};
 
-pub fn remove(packages: &[String], force: bool) -> cu::Result<()> {
+/// Remove the packages. With `recursive`, also remove the dependencies
+/// that are no longer needed by any explicitly installed package
+pub fn remove(packages: &[String], force: bool, dry_run: bool, recursive: bool) -> cu::Result<()> {
     let pkgs = graph::parse_pkgs(packages)?;
     let mut installed = InstallCache::load()?;
-    let pkgs = rectify_pkgs_to_remove(pkgs, &installed, force);
+    let mut pkgs = rectify_pkgs_to_remove(pkgs, &installed, force);
     if pkgs.is_empty() {
         cu::bail!("please specify packages to remove, see `shaft remove -h`");
     }
+    if recursive {
+        let orphans = graph::find_orphans(&installed, pkgs);
+        if !orphans.is_empty() {
+            let pkgs_string = orphans.iter().join(", ");
+            cu::info!("also removing dependencies no longer needed: [ {pkgs_string} ]");
+            pkgs |= orphans;
+        }
+    }
+    if dry_run {
+        return print_remove_plan(pkgs, &installed, force);
+    }
 
     let graph = graph::build_remove_graph(pkgs, &installed, &mut Default::default())?;
     match graph.len() {
@@ -40,13 +54,17 @@ pub fn remove(packages: &[String], force: bool) -> cu::Result<()> {
                     cu::warn!("'{pkg}' is not installed, skipping");
                     continue;
                 }
+                crate::history::record_package(pkg, "force remove", Some(&Verified::NotInstalled));
+            }
+            Ok(verified) => {
+                crate::history::record_package(pkg, "remove", Some(&verified));
             }
-            Ok(_) => {}
             Err(e) => {
                 if !force {
                     cu::rethrow!(e, "failed to verify package status (--force to bypass)");
                 }
                 cu::warn!("will force uninstall '{pkg}' because of error: {e:?}");
+                crate::history::record_package(pkg, "force remove", None);
             }
         }
         package.pre_uninstall(&ctx)?;
@@ -64,6 +82,8 @@ pub fn remove(packages: &[String], force: bool) -> cu::Result<()> {
     for pkg in to_uninstall {
         ctx.pkg = pkg;
         ctx = cu::check!(do_remove_package(ctx), "failed to remove '{pkg}'")?;
+        // verified as not installed when removing
+        crate::history::record_after(pkg, &Verified::NotInstalled);
         ctx.set_bar(None);
         installed.remove(pkg);
         ctx.set_installed(pkg, false);
@@ -87,6 +107,59 @@ pub fn remove(packages: &[String], force: bool) -> cu::Result<()> {
     Ok(())
 }
 
+/// Print what removing the packages would do, without changing anything
+fn print_remove_plan(
+    pkgs: EnumSet<PkgId>,
+    installed: &InstallCache,
+    force: bool,
+) -> cu::Result<()> {
+    cu::lv::disable_print_time();
+    let graph = graph::build_remove_graph(pkgs, installed, &mut Default::default())?;
+    if graph.is_empty() {
+        cu::info!("nothing to do");
+        return Ok(());
+    }
+
+    let mut ctx = Context::new(ItemMgr::default());
+    for pkg in installed.pkgs {
+        ctx.set_installed(pkg, true);
+    }
+    let package_field_width = graph
+        .iter()
+        .map(|x| x.to_str().len())
+        .max()
+        .unwrap_or(10)
+        .max(10);
+    cu::hint!(
+        "{:>package_field_width$} | action\n------------------------------------------------------------",
+        "package"
+    );
+    let mut uninstalled = EnumSet::new();
+    for pkg in graph {
+        ctx.pkg = pkg;
+        ctx.stage.set(Stage::Verify);
+        let (will_remove, action) = match pkg.package().verify(&ctx) {
+            Ok(Verified::NotInstalled) if !force => (false, "skip (not installed)".to_string()),
+            Ok(_) => (true, "remove".to_string()),
+            Err(e) if force => (true, format!("force remove (verify failed: {e})")),
+            Err(e) => (
+                false,
+                format!("error: verify failed, remove will stop here: {e}"),
+            ),
+        };
+        if will_remove {
+            uninstalled.insert(pkg);
+        }
+        cu::print!("{pkg:>package_field_width$} | {action}");
+    }
+    let sync_pkgs = graph::resolve_config_pkgs(EnumSet::new(), uninstalled, installed);
+    if !sync_pkgs.is_empty() {
+        let pkgs_string = sync_pkgs.iter().join(", ");
+        cu::hint!("re-configured because their config dependencies change: [ {pkgs_string} ]");
+    }
+    Ok(())
+}
+
 fn rectify_pkgs_to_remove(
     pkgs: EnumSet<PkgId>,
     installed: &InstallCache,
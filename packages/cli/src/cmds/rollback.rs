@@ -0,0 +1,165 @@
+use std::path::Path;
+
+use corelib::{ItemMgr, hmgr};
+use cu::pre::*;
+use itertools::Itertools as _;
+use registry::{Context, PkgId, Stage};
+
+use crate::graph::{self, InstallCache};
+
+/// Restore the previous installation of a package kept in HOME/install-old,
+/// and re-configure it. The package will not be upgraded by `sync` until it's
+/// synced explicitly.
+///
+/// The current installation is kept in HOME/install-old, so running rollback
+/// again will go back to it
+pub fn rollback(package: &str) -> cu::Result<()> {
+    let pkg = cu::check!(PkgId::from_str(package), "cannot find package '{package}'")?;
+    let mut installed = InstallCache::load()?;
+    if !installed.pkgs.contains(pkg) {
+        cu::bail!("'{pkg}' is not installed");
+    }
+    // check everything before changing anything
+    let install_dir = hmgr::paths::install_dir(pkg.to_str());
+    let old_install_dir = hmgr::paths::install_old_dir(pkg.to_str());
+    if !install_dir.exists() {
+        cu::bail!(
+            "'{pkg}' is not installed to '{}', only packages installed there can be rolled back",
+            hmgr::paths::install_root().display()
+        );
+    }
+    if !old_install_dir.exists() {
+        cu::hint!(
+            "the previous installation is only kept when the package is upgraded, and is deleted by `shaft clean`"
+        );
+        cu::bail!("there is no previous installation of '{pkg}' to roll back to");
+    }
+
+    crate::history::record_package(pkg, "rollback", None);
+    let bar = cu::progress(format!("rollback '{pkg}'")).spawn();
+    let mut ctx = Context::new(ItemMgr::load()?);
+    for pkg in installed.pkgs {
+        ctx.set_installed(pkg, true);
+    }
+    ctx.pkg = pkg;
+    ctx.set_bar(Some(&bar));
+    let ctx = &ctx;
+    let package = pkg.package();
+
+    cu::progress!(bar, "backup");
+    ctx.stage.set(Stage::Backup);
+    let mut backup_guard = package.backup_guard(ctx)?;
+
+    cu::progress!(bar, "restoring previous installation");
+    cu::check!(
+        cu::fs::make_dir(hmgr::paths::temp_root()),
+        "failed to create temp root"
+    )?;
+    let temp_dir = hmgr::paths::temp_dir("rollback");
+    swap_dirs(&install_dir, &old_install_dir, &temp_dir)?;
+
+    cu::progress!(bar, "configuring");
+    ctx.stage.set(Stage::Configure);
+    let result = (|| -> cu::Result<()> {
+        ctx.items_mut()?.remove_package(pkg.to_str())?;
+        package.configure(ctx)?;
+        ctx.items_mut()?.rebuild_items(Some(&bar))?;
+        Ok(())
+    })();
+    if let Err(e) = result {
+        // put the current installation back, the backup is restored by the guard
+        if let Err(e) = swap_dirs(&install_dir, &old_install_dir, &temp_dir) {
+            cu::error!("failed to undo the rollback of '{pkg}': {e:?}");
+        }
+        cu::rethrow!(
+            e,
+            "failed to configure the previous installation of '{pkg}'"
+        );
+    }
+    backup_guard.clear();
+    bar.done();
+
+    installed.set_dirty(pkg, false);
+    installed.update_config_hash(pkg);
+    installed.set_rolled_back(pkg, true);
+    // same as sync: dirty the config of inverted config dependencies
+    let mut dirtied = vec![];
+    for pkg2 in installed.pkgs {
+        if graph::config_dependencies(pkg2).contains(pkg) {
+            installed.set_dirty(pkg2, true);
+            dirtied.push(pkg2);
+        }
+    }
+    installed.save()?;
+
+    cu::info!("rolled back '{pkg}' to the previous installation");
+    cu::hint!("'{pkg}' will not be upgraded until running `shaft sync {pkg}`");
+    if !dirtied.is_empty() {
+        let pkgs_string = dirtied.iter().join(", ");
+        cu::hint!(
+            "run `shaft sync` to re-configure packages that depend on its config: [ {pkgs_string} ]"
+        );
+    }
+    Ok(())
+}
+
+/// Swap 2 existing directories, using `temp` as the intermediate location.
+/// Swapping again undoes the swap
+fn swap_dirs(a: &Path, b: &Path, temp: &Path) -> cu::Result<()> {
+    cu::fs::rec_remove(temp)?;
+    cu::check!(
+        cu::fs::rename(a, temp),
+        "failed to move '{}' out of the way",
+        a.display()
+    )?;
+    if let Err(e) = cu::fs::rename(b, a) {
+        if let Err(e) = cu::fs::rename(temp, a) {
+            cu::error!("failed to move '{}' back: {e:?}", a.display());
+        }
+        cu::rethrow!(e, "failed to move '{}' to '{}'", b.display(), a.display());
+    }
+    cu::check!(
+        cu::fs::rename(temp, b),
+        "failed to move '{}' to '{}'",
+        a.display(),
+        b.display()
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_swap_dirs() {
+        let root = std::env::temp_dir().join("shaft-rollback-test-swap");
+        cu::fs::make_dir_empty(&root).unwrap();
+        let current = root.join("install");
+        let old = root.join("install-old");
+        let temp = root.join("temp");
+        cu::fs::make_dir(&current).unwrap();
+        cu::fs::make_dir(&old).unwrap();
+        cu::fs::write(current.join("version"), "2").unwrap();
+        cu::fs::write(old.join("version"), "1").unwrap();
+        // leftover from a previous run should not get in the way
+        cu::fs::make_dir(&temp).unwrap();
+
+        swap_dirs(&current, &old, &temp).unwrap();
+        assert_eq!(cu::fs::read_string(current.join("version")).unwrap(), "1");
+        assert_eq!(cu::fs::read_string(old.join("version")).unwrap(), "2");
+        assert!(!temp.exists());
+
+        // rolling back again goes back to the newer installation
+        swap_dirs(&current, &old, &temp).unwrap();
+        assert_eq!(cu::fs::read_string(current.join("version")).unwrap(), "2");
+        assert_eq!(cu::fs::read_string(old.join("version")).unwrap(), "1");
+
+        // nothing is lost if the other directory is missing
+        cu::fs::rec_remove(&old).unwrap();
+        assert!(swap_dirs(&current, &old, &temp).is_err());
+        assert_eq!(cu::fs::read_string(current.join("version")).unwrap(), "2");
+
+        cu::fs::rec_remove(&root).unwrap();
+    }
+}
@@ -0,0 +1,102 @@
+use corelib::ItemMgr;
+use enumset::EnumSet;
+use itertools::Itertools as _;
+use registry::{Context, PkgId, Stage, Verified};
+
+use crate::graph::InstallCache;
+
+/// Print the health of installed packages, without changing anything.
+///
+/// If `check_untracked`, also verify the other packages in the registry
+/// to find the ones installed but not tracked by shaft
+pub fn status(check_untracked: bool) -> cu::Result<()> {
+    cu::lv::disable_print_time();
+    let installed = InstallCache::load()?;
+    let mut ctx = Context::new(ItemMgr::default());
+    for pkg in installed.pkgs {
+        ctx.set_installed(pkg, true);
+    }
+
+    let mut rows = Vec::with_capacity(installed.pkgs.len());
+    let mut needs_sync = false;
+    for pkg in installed.pkgs {
+        let status = match verify(&mut ctx, pkg) {
+            Ok(Verified::UpToDate(_)) => "installed".to_string(),
+            Ok(Verified::NotUpToDate(_)) => "outdated".to_string(),
+            Ok(Verified::NeedsConfig) => "needs-config".to_string(),
+            Ok(Verified::NotInstalled) => "missing".to_string(),
+            Err(e) => format!("error: {e}"),
+        };
+        needs_sync |= status != "installed" || installed.needs_config(pkg);
+        rows.push((pkg, status));
+    }
+
+    // packages that are on the system, but not recorded as installed
+    let mut untracked = EnumSet::new();
+    let not_installed = if check_untracked {
+        EnumSet::<PkgId>::all().difference(installed.pkgs)
+    } else {
+        EnumSet::new()
+    };
+    for pkg in not_installed {
+        if !pkg.package().enabled() {
+            continue;
+        }
+        match verify(&mut ctx, pkg) {
+            Ok(Verified::NotInstalled) => {}
+            Ok(_) => {
+                untracked.insert(pkg);
+            }
+            Err(e) => {
+                cu::debug!("failed to verify untracked package '{pkg}': {e:?}");
+            }
+        }
+    }
+
+    let package_field_width = rows
+        .iter()
+        .map(|(x, _)| x.to_str().len())
+        .max()
+        .unwrap_or(10)
+        .max(10);
+    cu::hint!(
+        "{:>package_field_width$} | dirtied | status\n------------------------------------------------------------",
+        "package"
+    );
+    for (pkg, status) in rows {
+        let is_dirtied = if installed.is_dirty(pkg) {
+            "yes"
+        } else if installed.is_config_edited(pkg) {
+            "edited"
+        } else {
+            "no"
+        };
+        cu::print!("{pkg:>package_field_width$} | {is_dirtied:>7} | {status}");
+    }
+
+    if !untracked.is_empty() {
+        let pkgs_string = untracked.iter().join(", ");
+        cu::warn!("installed but not in the install cache: [ {pkgs_string} ]");
+        cu::hint!("run `shaft sync <package>` to let shaft track them");
+    }
+    // dirty flags are only cleared when the package is synced or removed
+    let stale_dirty = installed.dirty.difference(installed.pkgs);
+    if !stale_dirty.is_empty() {
+        let pkgs_string = stale_dirty.iter().join(", ");
+        cu::warn!("dirtied but not installed: [ {pkgs_string} ]");
+    }
+    if needs_sync {
+        cu::hint!(
+            "run `shaft sync` to update the outdated, missing or dirtied packages, and re-configure the edited ones"
+        );
+    } else if untracked.is_empty() && stale_dirty.is_empty() {
+        cu::info!("all installed packages are up to date");
+    }
+    Ok(())
+}
+
+fn verify(ctx: &mut Context, pkg: PkgId) -> cu::Result<Verified> {
+    ctx.pkg = pkg;
+    ctx.stage.set(Stage::Verify);
+    pkg.package().verify(ctx)
+}
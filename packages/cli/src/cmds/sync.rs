@@ -1,23 +1,223 @@
-use corelib::{ItemMgr, VersionCache, hmgr};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use corelib::{ItemMgr, VersionCache, epkg, hmgr};
 use cu::pre::*;
+use enum_map::EnumMap;
 use enumset::EnumSet;
-use registry::{Context, PkgId, Stage, Verified};
+use itertools::Itertools as _;
+use registry::{BinId, Context, PackageRestoreGuard, PkgId, Stage, Verified};
 
-use crate::graph::{self, InstallCache};
+use crate::graph::{self, InstallCache, SyncJournal};
 
-pub fn sync(packages: &[String]) -> cu::Result<()> {
+pub fn sync(
+    packages: &[String],
+    providers: &[String],
+    dry_run: bool,
+    resume: bool,
+) -> cu::Result<()> {
+    if resume {
+        return resume_sync();
+    }
     let pkgs = graph::parse_pkgs(packages)?;
+    let mut provider_selection = EnumMap::default();
+    for provider in providers {
+        let (bin_id, pkg_id) = parse_provider(provider)?;
+        provider_selection[bin_id] = Some(pkg_id);
+    }
     let mut installed = InstallCache::load()?;
-    let pkgs = if pkgs.is_empty() {
+    // optional dependencies are only offered for the packages named explicitly
+    let named = !pkgs.is_empty();
+    let pkgs = if named {
+        // explicitly requested packages are upgraded even if rolled back or held
+        for pkg in pkgs {
+            installed.set_rolled_back(pkg, false);
+        }
+        installed.requested = pkgs;
+        pkgs
+    } else {
         // sync all installed packages
         installed.pkgs
+    };
+    if dry_run {
+        return print_sync_plan(pkgs, &installed, provider_selection, named);
+    }
+    sync_pkgs_impl(pkgs, &mut installed, provider_selection, named, true)
+}
+
+/// Continue the sync recorded in the journal
+fn resume_sync() -> cu::Result<()> {
+    let Some(mut journal) = SyncJournal::load()? else {
+        cu::bail!("there is no interrupted sync to resume");
+    };
+    // normally already restored when checking for interrupted sync on start up
+    journal.restore_pending_backups()?;
+    let remaining = journal
+        .graph
+        .iter()
+        .filter(|x| !journal.done.contains(**x))
+        .join(", ");
+    cu::info!("resuming sync, remaining: [ {remaining} ]");
+    let mut installed = InstallCache::load()?;
+    installed.requested = journal.requested;
+    // packages already done will be up-to-date
+    sync_pkgs_impl(
+        journal.pkgs,
+        &mut installed,
+        journal.providers,
+        false,
+        false,
+    )
+}
+
+/// Parse provider selection in the format of `<bin>=<pkg>`
+fn parse_provider(provider: &str) -> cu::Result<(BinId, PkgId)> {
+    let Some((bin, pkg)) = provider.split_once('=') else {
+        cu::bail!("invalid provider '{provider}', expected format: <binary>=<package>");
+    };
+    let (bin, pkg) = (bin.trim(), pkg.trim());
+    let bin_id = cu::check!(BinId::from_str(bin), "cannot find binary '{bin}'")?;
+    let pkg_id = cu::check!(PkgId::from_str(pkg), "cannot find package '{pkg}'")?;
+    if !bin_id.providers().contains(pkg_id) {
+        cu::bail!("package '{pkg_id}' does not provide binary '{bin_id}'");
+    }
+    Ok((bin_id, pkg_id))
+}
+
+/// Print what syncing the packages would do, without changing anything
+fn print_sync_plan(
+    pkgs: EnumSet<PkgId>,
+    installed: &InstallCache,
+    mut provider_selection: EnumMap<BinId, Option<PkgId>>,
+    hint_optional: bool,
+) -> cu::Result<()> {
+    cu::lv::disable_print_time();
+    if pkgs.is_empty() {
+        cu::info!("nothing to do");
+        return Ok(());
+    }
+    // simulate the dirty flags on a copy
+    let mut installed = installed.clone();
+    if let Err(e) = ItemMgr::load() {
+        cu::error!("{e:?}");
+        cu::warn!("items failed to load - all installed packages will be re-configured");
+        installed.dirty = installed.pkgs;
+    } else if let Some(false) = core_version_cache().is_uptodate()? {
+        cu::warn!("core version was bumped - all installed packages will be re-configured");
+        installed.dirty = installed.pkgs;
+    }
+    graph::preselect_providers(&mut provider_selection, &installed)?;
+    let optional = if hint_optional {
+        graph::OptionalDeps::Hint
     } else {
-        pkgs
+        graph::OptionalDeps::Skip
     };
-    sync_pkgs(pkgs, &mut installed)
+    let graph = graph::build_sync_graph(pkgs, &installed, &mut provider_selection, optional)?;
+
+    let mut ctx = Context::new(ItemMgr::default());
+    for pkg in installed.pkgs {
+        ctx.set_installed(pkg, true);
+    }
+    let mut plan = Vec::with_capacity(graph.len());
+    let mut config_dependents = EnumSet::new();
+    let mut downloads = vec![];
+    for (i, pkg) in graph.iter().copied().enumerate() {
+        ctx.pkg = pkg;
+        let sync_type = match resolve_sync_type(&ctx, &installed, None) {
+            Ok((_, x)) => x,
+            Err(e) => {
+                cu::error!("{e:?}");
+                plan.push((pkg, None));
+                continue;
+            }
+        };
+        if matches!(sync_type, SyncType::Full | SyncType::FullWithBackup) {
+            ctx.stage.set(Stage::Download);
+            match ctx.plan_downloads(|ctx| pkg.package().download(ctx)) {
+                Ok(planned) => downloads.extend(planned.into_iter().map(|x| (pkg, x))),
+                Err(e) => cu::warn!("failed to resolve downloads for '{pkg}': {e:?}"),
+            }
+        }
+        // same as sync_pkgs: dirty the config of inverted config dependencies
+        if !matches!(
+            sync_type,
+            SyncType::UpToDate | SyncType::RolledBack | SyncType::Held
+        ) {
+            for pkg2 in graph.iter().skip(i + 1).copied() {
+                if graph::config_dependencies(pkg2).contains(pkg) && !installed.is_dirty(pkg2) {
+                    installed.set_dirty(pkg2, true);
+                    config_dependents.insert(pkg2);
+                }
+            }
+        }
+        plan.push((pkg, Some(sync_type)));
+    }
+
+    let package_field_width = graph
+        .iter()
+        .map(|x| x.to_str().len())
+        .max()
+        .unwrap_or(10)
+        .max(10);
+    cu::hint!(
+        "{:>package_field_width$} | action\n------------------------------------------------------------",
+        "package"
+    );
+    for (pkg, sync_type) in plan {
+        let action = match sync_type {
+            None => "error: verify failed, sync will stop here".to_string(),
+            Some(x) => x.to_string(),
+        };
+        cu::print!("{pkg:>package_field_width$} | {action}");
+    }
+    if !config_dependents.is_empty() {
+        let pkgs_string = config_dependents.iter().join(", ");
+        cu::hint!("re-configured because their config dependencies change: [ {pkgs_string} ]");
+    }
+    if downloads.is_empty() {
+        cu::hint!("no download needed");
+    } else {
+        cu::hint!("downloads not in the cache yet:");
+        for (pkg, download) in downloads {
+            cu::print!(
+                "  {pkg}: {} from {}",
+                download.identifier.display(),
+                download.url
+            );
+        }
+    }
+    Ok(())
+}
+
+fn core_version_cache() -> VersionCache {
+    VersionCache::new("registry::CORE_VERSION", registry::CORE_VERSION)
 }
 
 pub fn sync_pkgs(pkgs: EnumSet<PkgId>, installed: &mut InstallCache) -> cu::Result<()> {
+    sync_pkgs_with_providers(pkgs, installed, Default::default())
+}
+
+/// Sync the packages, with providers preselected for some binaries
+pub fn sync_pkgs_with_providers(
+    pkgs: EnumSet<PkgId>,
+    installed: &mut InstallCache,
+    provider_selection: EnumMap<BinId, Option<PkgId>>,
+) -> cu::Result<()> {
+    sync_pkgs_impl(pkgs, installed, provider_selection, false, false)
+}
+
+/// Sync the packages, and if `offer_optional`, ask whether to install
+/// the missing optional dependencies of the packages.
+///
+/// If `save_providers`, the preselected providers are saved to the config
+/// once the sync graph is resolved
+fn sync_pkgs_impl(
+    pkgs: EnumSet<PkgId>,
+    installed: &mut InstallCache,
+    mut provider_selection: EnumMap<BinId, Option<PkgId>>,
+    offer_optional: bool,
+    save_providers: bool,
+) -> cu::Result<()> {
     if pkgs.is_empty() {
         return Ok(());
     }
@@ -31,7 +231,7 @@ pub fn sync_pkgs(pkgs: EnumSet<PkgId>, installed: &mut InstallCache) -> cu::Resu
             None
         }
     };
-    let core_version_cache = VersionCache::new("registry::CORE_VERSION", registry::CORE_VERSION);
+    let core_version_cache = core_version_cache();
     if let Some(items2) = &mut items {
         let core_version_uptodate = core_version_cache.is_uptodate()?;
         if let Some(false) = core_version_uptodate {
@@ -55,120 +255,418 @@ pub fn sync_pkgs(pkgs: EnumSet<PkgId>, installed: &mut InstallCache) -> cu::Resu
             ItemMgr::load()?
         }
     };
-    let graph = graph::build_sync_graph(pkgs, installed, &mut Default::default())?;
+    let selected_providers = provider_selection;
+    graph::preselect_providers(&mut provider_selection, installed)?;
+    let mut accepted = EnumSet::new();
+    let optional = if offer_optional {
+        graph::OptionalDeps::Offer(&mut accepted)
+    } else {
+        graph::OptionalDeps::Skip
+    };
+    let graph = graph::build_sync_graph(pkgs, installed, &mut provider_selection, optional)?;
+    if save_providers {
+        for (bin_id, pkg_id) in &selected_providers {
+            if let Some(pkg_id) = pkg_id {
+                crate::config::save_provider(bin_id, *pkg_id)?;
+            }
+        }
+    }
+    // accepted optional dependencies are installed as if requested explicitly
+    let pkgs = pkgs | accepted;
+    installed.requested.insert_all(accepted);
     match graph.len() {
         1 => cu::info!("syncing 1 package..."),
         x => cu::info!("syncing {x} packages..."),
     }
+    let journal = SyncJournal::new(pkgs, installed.requested, provider_selection, graph.clone());
+    journal.save()?;
+    let journal = Mutex::new(journal);
     let mut ctx = Context::new(items);
     for pkg in installed.pkgs {
         ctx.set_installed(pkg, true);
     }
+    // packages are verified when planning the downloads, and the results
+    // are reused when installing them
+    let mut verified = EnumMap::default();
+    if let Err(e) = download_all(&mut ctx, &graph, installed, &mut verified) {
+        cu::warn!("{e:?}");
+        cu::warn!("failed downloads will be retried when syncing the packages");
+    }
 
-    for (i, pkg) in graph.iter().copied().enumerate() {
-        ctx.pkg = pkg;
-        let result = do_sync_package(ctx, installed);
-        let result = cu::check!(result, "failed to sync '{pkg}'")?;
-        ctx = result.1;
-        ctx.set_bar(None);
-        installed.add(pkg)?;
-        ctx.set_installed(pkg, true);
-        // dirty the config of inverted config dependencies
-        if !matches!(result.0, SyncType::UpToDate) {
-            for pkg2 in graph.iter().skip(i + 1).copied() {
-                if pkg2.package().config_dependencies().contains(pkg) {
-                    installed.set_dirty(pkg2, true);
+    for wave in graph::sync_waves(&graph, &provider_selection) {
+        // packages in the same wave do not depend on each other,
+        // so they can be installed at the same time
+        let results = install_wave(&wave, installed, &journal, &verified);
+        // config items and the install cache are changed one package at a time,
+        // and the rest of the wave is not configured once a package fails
+        let mut first_error: Option<(PkgId, cu::Error)> = None;
+        for (pkg, result) in wave.iter().copied().zip(results) {
+            ctx.pkg = pkg;
+            let result = match (&first_error, result) {
+                (None, result) => {
+                    result.and_then(|x| configure_package(&mut ctx, installed, &journal, x))
+                }
+                (Some((failed, _)), Ok(x)) => {
+                    cu::warn!("not configuring '{pkg}' since '{failed}' failed to sync");
+                    restore_package(&mut ctx, x);
+                    lock_journal(&journal).fail(pkg)?;
+                    continue;
+                }
+                (Some(_), Err(e)) => Err(e),
+            };
+            ctx.set_bar(None);
+            let sync_type = match result {
+                Ok(x) => x,
+                Err(e) => {
+                    lock_journal(&journal).fail(pkg)?;
+                    if first_error.is_some() {
+                        cu::error!("failed to sync '{pkg}': {e:?}");
+                    } else {
+                        first_error = Some((pkg, e));
+                    }
+                    continue;
+                }
+            };
+            installed.add(pkg)?;
+            if installed.requested.contains(pkg) {
+                installed.explicit.insert(pkg);
+            }
+            ctx.set_installed(pkg, true);
+            // dirty the config of inverted config dependencies
+            if !matches!(
+                sync_type,
+                SyncType::UpToDate | SyncType::RolledBack | SyncType::Held
+            ) {
+                for pkg2 in graph.iter().skip_while(|x| **x != pkg).skip(1).copied() {
+                    if graph::config_dependencies(pkg2).contains(pkg) {
+                        installed.set_dirty(pkg2, true);
+                    }
                 }
             }
+            installed.save()?;
+            lock_journal(&journal).finish(pkg)?;
+        }
+        if let Some((pkg, e)) = first_error {
+            cu::hint!("run `shaft sync --resume` to retry from '{pkg}'");
+            cu::rethrow!(e, "failed to sync '{pkg}'");
         }
-        installed.save()?;
     }
+    SyncJournal::remove()?;
 
     Ok(())
 }
 
-fn do_sync_package(
-    mut ctx: Context,
-    installed: &mut InstallCache,
-) -> cu::Result<(SyncType, Context)> {
-    let pkg = ctx.pkg;
-    let package = ctx.pkg.package();
-    ctx.stage.set(Stage::Verify);
+/// Max number of packages to install at the same time
+const MAX_CONCURRENT_SYNCS: usize = 4;
 
-    let sync_type = match package.verify(&ctx)? {
+/// Run the stages before configure for the packages in the wave concurrently.
+/// Returns the result for each package in the same order as the wave
+fn install_wave(
+    wave: &[PkgId],
+    installed: &InstallCache,
+    journal: &Mutex<SyncJournal>,
+    verified: &EnumMap<PkgId, Option<Verified>>,
+) -> Vec<cu::Result<InstalledPackage>> {
+    if let [pkg] = wave {
+        return vec![install_package(
+            *pkg,
+            installed,
+            journal,
+            verified[*pkg].clone(),
+        )];
+    }
+    let next = AtomicUsize::new(0);
+    let mut results = std::thread::scope(|s| {
+        let workers = (0..MAX_CONCURRENT_SYNCS.min(wave.len()))
+            .map(|_| {
+                s.spawn(|| {
+                    let mut results = vec![];
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(pkg) = wave.get(i) else {
+                            return results;
+                        };
+                        let verified = verified[*pkg].clone();
+                        results.push((i, install_package(*pkg, installed, journal, verified)));
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        workers
+            .into_iter()
+            .flat_map(|x| match x.join() {
+                Ok(x) => x,
+                Err(e) => std::panic::resume_unwind(e),
+            })
+            .collect::<Vec<_>>()
+    });
+    results.sort_by_key(|x| x.0);
+    results.into_iter().map(|x| x.1).collect()
+}
+
+fn lock_journal(journal: &Mutex<SyncJournal>) -> MutexGuard<'_, SyncJournal> {
+    journal.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Max number of files to download at the same time
+const MAX_CONCURRENT_DOWNLOADS: usize = 4;
+
+/// Download the files needed by all packages in the graph concurrently,
+/// before installing them one by one. The packages are verified to find
+/// the ones to download, and the results are stored in `verified`
+fn download_all(
+    ctx: &mut Context,
+    graph: &[PkgId],
+    installed: &InstallCache,
+    verified: &mut EnumMap<PkgId, Option<Verified>>,
+) -> cu::Result<()> {
+    let mut downloads: Vec<hmgr::PlannedDownload> = vec![];
+    for pkg in graph.iter().copied() {
+        ctx.pkg = pkg;
+        // errors are reported when syncing the package
+        let Ok((verified_pkg, sync_type)) = resolve_sync_type(ctx, installed, None) else {
+            continue;
+        };
+        verified[pkg] = Some(verified_pkg);
+        if !matches!(sync_type, SyncType::Full | SyncType::FullWithBackup) {
+            continue;
+        }
+        ctx.stage.set(Stage::Download);
+        let planned = match ctx.plan_downloads(|ctx| pkg.package().download(ctx)) {
+            Ok(x) => x,
+            Err(e) => {
+                cu::debug!("failed to resolve downloads for '{pkg}': {e:?}");
+                continue;
+            }
+        };
+        for download in planned {
+            if !downloads.iter().any(|x| x.path == download.path) {
+                downloads.push(download);
+            }
+        }
+    }
+    hmgr::download_planned(&downloads, MAX_CONCURRENT_DOWNLOADS, None)
+}
+
+/// Verify the package in the context and determine what syncing it would do.
+///
+/// `verified` is the result of verifying the package earlier in the same sync, if any,
+/// in which case the package is not verified again
+fn resolve_sync_type(
+    ctx: &Context,
+    installed: &InstallCache,
+    verified: Option<Verified>,
+) -> cu::Result<(Verified, SyncType)> {
+    let pkg = ctx.pkg;
+    let verified = match verified {
+        Some(x) => x,
+        None => {
+            ctx.stage.set(Stage::Verify);
+            pkg.package().verify(ctx)?
+        }
+    };
+    let sync_type = match verified {
         Verified::NotInstalled => SyncType::Full,
-        Verified::NotUpToDate => SyncType::FullWithBackup,
+        Verified::NotUpToDate(_) if installed.is_frozen(pkg) => {
+            // keep the installed version until the package is synced explicitly,
+            // but still re-configure if needed
+            if installed.needs_config(pkg) {
+                SyncType::Config
+            } else if installed.is_rolled_back(pkg) {
+                SyncType::RolledBack
+            } else {
+                SyncType::Held
+            }
+        }
+        Verified::NotUpToDate(_) => SyncType::FullWithBackup,
         Verified::NeedsConfig => SyncType::Config,
-        Verified::UpToDate => {
+        Verified::UpToDate(_) => {
             // if the pkg is not installed yet (meaning never configured),
             // configure it even if the binaries are installed already
             //
-            // and reconfigure if the config is dirtied
-            if !installed.pkgs.contains(pkg) || installed.is_dirty(pkg) {
+            // and reconfigure if the config is dirtied or edited
+            if !installed.pkgs.contains(pkg) || installed.needs_config(pkg) {
                 SyncType::Config
             } else {
                 SyncType::UpToDate
             }
         }
     };
+    Ok((verified, sync_type))
+}
 
-    let (bar, mut backup_guard) = match sync_type {
+/// A package that finished the stages before configure
+struct InstalledPackage {
+    sync_type: SyncType,
+    /// Progress bar of the package, `None` if there's nothing more to do
+    bar: Option<Arc<cu::ProgressBar>>,
+    /// If a backup is taken, and needs to be restored if configuring fails
+    backup: bool,
+}
+
+/// Verify, backup, download and install the package, with its own context.
+/// Config items are not available, since they may only be changed during configure.
+///
+/// The package is not verified again if `verified` is already known
+fn install_package(
+    pkg: PkgId,
+    installed: &InstallCache,
+    journal: &Mutex<SyncJournal>,
+    verified: Option<Verified>,
+) -> cu::Result<InstalledPackage> {
+    // keep the external package managers until the package is installed,
+    // so they are not used by other packages in between
+    let _session = epkg::begin_package_session();
+    let package = pkg.package();
+    let mut ctx = Context::new(ItemMgr::default());
+    for pkg in installed.pkgs {
+        ctx.set_installed(pkg, true);
+    }
+    ctx.pkg = pkg;
+    lock_journal(journal).set_stage(pkg, Stage::Verify)?;
+    let (verified, sync_type) = resolve_sync_type(&ctx, installed, verified)?;
+    if !matches!(sync_type, SyncType::UpToDate) {
+        crate::history::record_package(pkg, sync_type, Some(&verified));
+    }
+
+    let bar = match sync_type {
         SyncType::UpToDate => {
             cu::info!("up to date: '{pkg}'");
-            return Ok((sync_type, ctx));
+            None
+        }
+        SyncType::RolledBack => {
+            cu::info!("rolled back, not upgrading: '{pkg}'");
+            cu::hint!("run `shaft sync {pkg}` to upgrade it");
+            None
+        }
+        SyncType::Held => {
+            cu::info!("held, not upgrading: '{pkg}'");
+            cu::hint!("run `shaft sync {pkg}` to upgrade it once, or `shaft unhold {pkg}`");
+            None
         }
         SyncType::Config => {
             cu::debug!("sync type for '{pkg}': config");
-            let bar = cu::progress(format!("config '{pkg}'")).spawn();
-            ctx.set_bar(Some(&bar));
-            (bar, None)
+            Some(cu::progress(format!("config '{pkg}'")).spawn())
         }
         SyncType::FullWithBackup => {
             cu::debug!("sync type for '{pkg}': full-backup");
-            let bar = cu::progress(format!("sync '{pkg}'")).spawn();
-            ctx.set_bar(Some(&bar));
-
-            cu::progress!(bar, "backup");
-            ctx.stage.set(Stage::Backup);
-            (bar, Some(package.backup_guard(&ctx)?))
+            Some(cu::progress(format!("sync '{pkg}'")).spawn())
         }
         SyncType::Full => {
             cu::debug!("sync type for '{pkg}': full");
-            let bar = cu::progress(format!("sync '{pkg}'")).spawn();
-            ctx.set_bar(Some(&bar));
-            (bar, None)
+            Some(cu::progress(format!("sync '{pkg}'")).spawn())
         }
     };
+    let Some(bar) = bar else {
+        return Ok(InstalledPackage {
+            sync_type,
+            bar: None,
+            backup: false,
+        });
+    };
+    if matches!(sync_type, SyncType::Config) {
+        return Ok(InstalledPackage {
+            sync_type,
+            bar: Some(bar),
+            backup: false,
+        });
+    }
+    ctx.set_bar(Some(&bar));
 
-    if !matches!(sync_type, SyncType::Config) {
-        cu::progress!(bar, "downloading");
-        ctx.stage.set(Stage::Download);
-        package.download(&ctx)?;
+    let mut backup_guard = None;
+    if matches!(sync_type, SyncType::FullWithBackup) {
+        cu::progress!(bar, "backup");
+        ctx.stage.set(Stage::Backup);
+        lock_journal(journal).set_stage(pkg, Stage::Backup)?;
+        backup_guard = Some(package.backup_guard(&ctx)?);
+        lock_journal(journal).set_backup(pkg, true)?;
+    }
+
+    cu::progress!(bar, "downloading");
+    ctx.stage.set(Stage::Download);
+    lock_journal(journal).set_stage(pkg, Stage::Download)?;
+    package.download(&ctx)?;
+
+    cu::progress!(bar, "installing");
+    ctx.stage.set(Stage::Install);
+    lock_journal(journal).set_stage(pkg, Stage::Install)?;
+    package.install(&ctx)?;
+
+    // the guard borrows the context of this thread, a new one is
+    // created when configuring
+    let backup = match backup_guard {
+        Some(mut x) => {
+            x.clear();
+            true
+        }
+        None => false,
+    };
+    Ok(InstalledPackage {
+        sync_type,
+        bar: Some(bar),
+        backup,
+    })
+}
 
-        cu::progress!(bar, "installing");
-        ctx.stage.set(Stage::Install);
-        package.install(&ctx)?;
+/// Restore the backup taken by [`install_package`], without configuring the package
+fn restore_package(ctx: &mut Context, installed_package: InstalledPackage) {
+    let Some(bar) = installed_package.bar else {
+        return;
+    };
+    if installed_package.backup {
+        ctx.set_bar(Some(&bar));
+        drop(PackageRestoreGuard::new(ctx.pkg.package(), ctx));
+        ctx.set_bar(None);
     }
+}
+
+/// Configure, clean and verify the package installed by [`install_package`]
+fn configure_package(
+    ctx: &mut Context,
+    installed: &mut InstallCache,
+    journal: &Mutex<SyncJournal>,
+    installed_package: InstalledPackage,
+) -> cu::Result<SyncType> {
+    let pkg = ctx.pkg;
+    let package = pkg.package();
+    let sync_type = installed_package.sync_type;
+    let Some(bar) = installed_package.bar else {
+        return Ok(sync_type);
+    };
+    ctx.set_bar(Some(&bar));
+    let ctx = &*ctx;
+    let mut backup_guard = installed_package
+        .backup
+        .then(|| PackageRestoreGuard::new(package, ctx));
 
     cu::progress!(bar, "configuring");
     ctx.stage.set(Stage::Configure);
+    lock_journal(journal).set_stage(pkg, Stage::Configure)?;
     ctx.items_mut()?.remove_package(pkg.to_str())?;
-    package.configure(&ctx)?;
+    package.configure(ctx)?;
     ctx.items_mut()?.rebuild_items(Some(&bar))?;
     installed.set_dirty(pkg, false);
+    installed.update_config_hash(pkg);
 
     cu::progress!(bar, "cleaning");
     ctx.stage.set(Stage::Clean);
-    package.clean(&ctx)?;
+    lock_journal(journal).set_stage(pkg, Stage::Clean)?;
+    package.clean(ctx)?;
 
     cu::progress!(bar, "verifying");
-    match package.verify(&ctx)? {
-        Verified::UpToDate => {
+    let verified = package.verify(ctx)?;
+    crate::history::record_after(pkg, &verified);
+    match verified {
+        Verified::UpToDate(_) => {
             bar.done();
             if let Some(mut x) = backup_guard.take() {
                 x.clear();
+                lock_journal(journal).set_backup(pkg, false)?;
             }
         }
+        Verified::NotUpToDate(_) if installed.is_frozen(pkg) => {
+            bar.done();
+        }
         _ => {
             cu::hint!(
                 r"verification failed after installation
@@ -188,16 +686,27 @@ such as pacman and cargo.
     }
     drop(backup_guard);
 
-    Ok((sync_type, ctx))
+    Ok(sync_type)
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
 enum SyncType {
     /// already up-to-date, nothing to do
+    #[display("up-to-date")]
     UpToDate,
+    /// Outdated, but rolled back to the previous installation, nothing to do
+    #[display("rolled back, not upgrading")]
+    RolledBack,
+    /// Outdated, but held at the installed version, nothing to do
+    #[display("held, not upgrading")]
+    Held,
     /// Just run the config stage to refresh the config
+    #[display("config")]
     Config,
     /// Full sync - download and install
+    #[display("full")]
     Full,
     /// Full sync - download and install, and also backup the old installation
+    #[display("full (with backup)")]
     FullWithBackup,
 }
@@ -0,0 +1,134 @@
+use cu::pre::*;
+use itertools::Itertools as _;
+use registry::{BinId, PkgId};
+
+use crate::graph::InstallCache;
+
+/// Explain why a package or binary is installed, by printing the
+/// dependency chains from installed packages that lead to it
+pub fn why(query: &str) -> cu::Result<()> {
+    cu::lv::disable_print_time();
+    let query = query.trim();
+    let installed = InstallCache::load()?;
+    let pkg = match PkgId::from_str(query) {
+        Some(pkg) => pkg,
+        None => {
+            let bin = cu::check!(
+                BinId::from_str(query),
+                "cannot find package or binary '{query}'"
+            )?;
+            let Some(pkg) = installed.bins[bin] else {
+                let providers = bin.providers().iter().join(", ");
+                cu::info!("binary '{bin}' is not provided by any installed package");
+                cu::hint!("packages that can provide it: [ {providers} ]");
+                return Ok(());
+            };
+            cu::info!("binary '{bin}' is provided by '{pkg}'");
+            pkg
+        }
+    };
+    if !installed.pkgs.contains(pkg) {
+        cu::warn!("'{pkg}' is not installed");
+    } else if installed.explicit.contains(pkg) {
+        cu::info!("'{pkg}' was installed explicitly");
+    } else {
+        cu::info!("'{pkg}' was installed as a dependency");
+    }
+
+    let bins = pkg.package().binaries();
+    if !bins.is_empty() {
+        let bin_field_width = bins
+            .iter()
+            .map(|x| x.to_str().len())
+            .max()
+            .unwrap_or(10)
+            .max(10);
+        cu::hint!(
+            "{:>bin_field_width$} | provided by\n------------------------------------------------------------",
+            "binary"
+        );
+        for bin in bins {
+            let provider = match installed.bins[bin] {
+                Some(x) => x.to_str(),
+                None => "(none)",
+            };
+            cu::print!("{bin:>bin_field_width$} | {provider}");
+        }
+    }
+
+    let mut chains = vec![];
+    collect_chains(&installed, &mut vec![pkg], &mut vec![], &mut chains);
+    if chains.is_empty() {
+        cu::info!("'{pkg}' is not required by any other installed package");
+        return Ok(());
+    }
+    cu::info!("'{pkg}' is required by:");
+    for chain in chains {
+        cu::print!("  {chain}");
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Dependency {
+    /// Depends on a binary provided by the package
+    Binary(BinId),
+    /// Depends on the config of the package
+    Config,
+}
+
+/// Get installed packages that directly depend on the package
+fn dependents(pkg: PkgId, installed: &InstallCache) -> Vec<(PkgId, Dependency)> {
+    let mut out = vec![];
+    for dependent in installed.pkgs {
+        if dependent == pkg {
+            continue;
+        }
+        let package = dependent.package();
+        for bin in package.binary_dependencies() {
+            if installed.bins[bin] == Some(pkg) {
+                out.push((dependent, Dependency::Binary(bin)));
+            }
+        }
+        if package.config_dependencies().contains(pkg) {
+            out.push((dependent, Dependency::Config));
+        }
+    }
+    out
+}
+
+/// Walk the dependents backwards, and collect the formatted chains.
+///
+/// `deps[i]` is how `path[i+1]` depends on `path[i]`
+fn collect_chains(
+    installed: &InstallCache,
+    path: &mut Vec<PkgId>,
+    deps: &mut Vec<Dependency>,
+    out: &mut Vec<String>,
+) {
+    let pkg = *path.last().unwrap();
+    let mut is_end = true;
+    for (dependent, dep) in dependents(pkg, installed) {
+        if path.contains(&dependent) {
+            // cycle
+            continue;
+        }
+        is_end = false;
+        path.push(dependent);
+        deps.push(dep);
+        collect_chains(installed, path, deps, out);
+        path.pop();
+        deps.pop();
+    }
+    if !is_end || deps.is_empty() {
+        return;
+    }
+    let mut chain = path.last().unwrap().to_string();
+    for (pkg, dep) in path.iter().zip(deps.iter()).rev() {
+        match dep {
+            Dependency::Binary(bin) => chain.push_str(&format!(" -> {bin} -> {pkg}")),
+            Dependency::Config => chain.push_str(&format!(" -(config)-> {pkg}")),
+        }
+    }
+    out.push(chain);
+}
@@ -1,10 +1,15 @@
+use std::collections::BTreeMap;
+
 use corelib::hmgr;
 use cu::pre::*;
+use registry::{BinId, PkgId};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct Config {
     pub windows: WindowsConfig,
+    #[serde(default)]
+    pub providers: ProvidersConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,6 +19,29 @@ pub struct WindowsConfig {
     pub control_home: bool,
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ProvidersConfig {
+    /// How to select a provider when it's not selected and not already installed
+    #[serde(default)]
+    pub policy: ProviderPolicy,
+    /// Binary mapping to the package selected to provide it
+    #[serde(flatten)]
+    pub select: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProviderPolicy {
+    /// Prompt for the provider
+    #[default]
+    Prompt,
+    /// Prefer `system-*` packages, which use an existing installation on the system
+    PreferSystem,
+    /// Prefer packages that are not `system-*`, which are managed by shaft
+    PreferShaft,
+}
+
 #[cu::context("failed to load config")]
 pub fn load_config() -> cu::Result<Config> {
     let path = hmgr::paths::config_toml();
@@ -24,3 +52,51 @@ pub fn load_config() -> cu::Result<Config> {
     let config = toml::parse::<Config>(&config_content)?;
     Ok(config)
 }
+
+/// Save the provider selection for a binary in the `[providers]` table of the config
+#[cu::context("failed to save provider for '{bin}' in config")]
+pub fn save_provider(bin: BinId, pkg: PkgId) -> cu::Result<()> {
+    // ensure the config exists and is valid
+    load_config()?;
+    let path = hmgr::paths::config_toml();
+    let config_content = cu::fs::read_string(&path)?;
+    let config_content = set_provider_entry(&config_content, bin.to_str(), pkg.to_str());
+    toml::parse::<Config>(&config_content)?;
+    cu::fs::write(&path, config_content)?;
+    Ok(())
+}
+
+/// Set the `bin = "pkg"` entry in the `[providers]` table of the TOML content,
+/// while keeping everything else (including comments) intact
+fn set_provider_entry(content: &str, bin: &str, pkg: &str) -> String {
+    let mut lines = content.lines().collect::<Vec<_>>();
+    let start = lines.iter().position(|x| x.trim() == "[providers]");
+    let entry = format!("{bin} = \"{pkg}\"");
+    let Some(start) = start else {
+        let mut out = content.trim_end().to_string();
+        out.push_str("\n\n[providers]\n");
+        out.push_str(&entry);
+        out.push('\n');
+        return out;
+    };
+    // remove the existing entry
+    let mut end = start + 1;
+    while end < lines.len() && !lines[end].trim_start().starts_with('[') {
+        let key = lines[end]
+            .split_once('=')
+            .map(|(k, _)| k.trim().trim_matches(['"', '\'']));
+        if key == Some(bin) {
+            lines.remove(end);
+        } else {
+            end += 1;
+        }
+    }
+    // add to the end of the section, before empty lines
+    while end > start + 1 && lines[end - 1].trim().is_empty() {
+        end -= 1;
+    }
+    lines.insert(end, &entry);
+    let mut out = lines.join("\n");
+    out.push('\n');
+    out
+}
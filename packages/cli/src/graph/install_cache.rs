@@ -1,17 +1,32 @@
 use std::collections::BTreeMap;
 
-use corelib::hmgr;
+use corelib::{ItemMgr, hmgr, opfs};
 use cu::pre::*;
 use enum_map::EnumMap;
 use enumset::EnumSet;
-use registry::{BinId, PkgId};
+use itertools::Itertools as _;
+use registry::{BinId, Context, PkgId, Stage};
 
 #[derive(Debug, Default, Clone)]
 pub struct InstallCache {
     /// Set of packages installed
     pub pkgs: EnumSet<PkgId>,
+    /// Set of packages installed because they were requested explicitly,
+    /// the rest are installed as dependencies
+    pub explicit: EnumSet<PkgId>,
     /// Set of packages with dirtied configs
     pub dirty: EnumSet<PkgId>,
+    /// Set of packages rolled back to the previous installation,
+    /// which should not be upgraded unless requested explicitly
+    pub rolled_back: EnumSet<PkgId>,
+    /// Set of packages held at the installed version,
+    /// which should not be upgraded unless requested explicitly
+    pub held: EnumSet<PkgId>,
+    /// Hash of the config file of each package when it was last configured,
+    /// used to detect edits made outside of shaft
+    pub config_hashes: EnumMap<PkgId, Option<String>>,
+    /// Packages requested explicitly by the current command. Not saved
+    pub requested: EnumSet<PkgId>,
     /// Binaries available mapping to the package that provides it
     pub bins: EnumMap<BinId, Option<PkgId>>,
 }
@@ -45,7 +60,7 @@ impl InstallCache {
         for new_pkg_id in new_pkg_ids {
             if self.pkgs.contains(new_pkg_id) {
                 // already installed - no conflict
-                return Ok(());
+                continue;
             }
 
             for bin_id in new_pkg_id.package().binaries() {
@@ -55,6 +70,13 @@ impl InstallCache {
                     );
                 }
             }
+            for existing_pkg_id in self.pkgs {
+                if is_declared_conflict(new_pkg_id, existing_pkg_id) {
+                    cu::bail!(
+                        "package '{new_pkg_id}' conflicts with the '{existing_pkg_id}' package currently installed."
+                    );
+                }
+            }
         }
         // check for conflicts among new packages
         let mut new_bin_ids: EnumMap<BinId, Option<PkgId>> = EnumMap::default();
@@ -68,6 +90,13 @@ impl InstallCache {
                 new_bin_ids[bin_id] = Some(new_pkg_id);
             }
         }
+        for [a, b] in new_pkg_ids.iter().array_combinations() {
+            if is_declared_conflict(a, b) {
+                cu::bail!(
+                    "package '{a}' and package '{b}' conflict with each other, only one of them can be installed."
+                );
+            }
+        }
 
         Ok(())
     }
@@ -88,7 +117,11 @@ impl InstallCache {
     }
 
     pub fn remove(&mut self, pkg: PkgId) {
+        self.explicit.remove(pkg);
         self.dirty.remove(pkg);
+        self.rolled_back.remove(pkg);
+        self.held.remove(pkg);
+        self.config_hashes[pkg] = None;
         if !self.pkgs.remove(pkg) {
             // was not installed, no-op
             return;
@@ -111,6 +144,81 @@ impl InstallCache {
             self.dirty.remove(pkg);
         }
     }
+
+    pub fn is_rolled_back(&self, pkg: PkgId) -> bool {
+        self.rolled_back.contains(pkg)
+    }
+
+    pub fn set_rolled_back(&mut self, pkg: PkgId, rolled_back: bool) {
+        if rolled_back {
+            self.rolled_back.insert(pkg);
+        } else {
+            self.rolled_back.remove(pkg);
+        }
+    }
+
+    pub fn is_held(&self, pkg: PkgId) -> bool {
+        self.held.contains(pkg)
+    }
+
+    pub fn set_held(&mut self, pkg: PkgId, held: bool) {
+        if held {
+            self.held.insert(pkg);
+        } else {
+            self.held.remove(pkg);
+        }
+    }
+
+    /// Check if the config file of the package is changed since it was last configured.
+    /// Always `false` if the hash was not recorded
+    pub fn is_config_edited(&self, pkg: PkgId) -> bool {
+        let Some(hash) = &self.config_hashes[pkg] else {
+            return false;
+        };
+        config_file_hash(pkg).as_ref() != Some(hash)
+    }
+
+    /// Check if the package needs to be re-configured, because the config
+    /// is dirtied or edited
+    pub fn needs_config(&self, pkg: PkgId) -> bool {
+        self.is_dirty(pkg) || self.is_config_edited(pkg)
+    }
+
+    /// Record the hash of the config file after the package is configured
+    pub fn update_config_hash(&mut self, pkg: PkgId) {
+        self.config_hashes[pkg] = config_file_hash(pkg);
+    }
+
+    /// Check if the package should be kept at the installed version,
+    /// because it's rolled back, or held and not requested explicitly
+    pub fn is_frozen(&self, pkg: PkgId) -> bool {
+        self.is_rolled_back(pkg) || (self.is_held(pkg) && !self.requested.contains(pkg))
+    }
+}
+
+/// Hash the config file of the package, `None` if the package does not have
+/// a config file (or the config location is a directory)
+fn config_file_hash(pkg: PkgId) -> Option<String> {
+    let mut ctx = Context::new(ItemMgr::default());
+    ctx.pkg = pkg;
+    ctx.stage.set(Stage::Configure);
+    let path = match pkg.package().config_location(&ctx) {
+        Ok(x) => x?,
+        Err(e) => {
+            cu::debug!("{e:?}");
+            return None;
+        }
+    };
+    if !path.is_file() {
+        return None;
+    }
+    match opfs::file_sha256(&path, None) {
+        Ok(x) => Some(x),
+        Err(e) => {
+            cu::debug!("{e:?}");
+            None
+        }
+    }
 }
 
 impl From<&InstallCacheJson> for InstallCache {
@@ -122,6 +230,16 @@ impl From<&InstallCacheJson> for InstallCache {
             };
             pkgs.insert(pkg_id);
         }
+        let explicit = match &value.explicit {
+            Some(names) => names
+                .iter()
+                .filter_map(|x| PkgId::from_str(x))
+                .filter(|x| pkgs.contains(*x))
+                .collect(),
+            // install reason was not recorded, assume everything is explicit
+            // so nothing is removed as orphan
+            None => pkgs,
+        };
         let mut dirty = EnumSet::new();
         for name in &value.dirty {
             let Some(pkg_id) = PkgId::from_str(name) else {
@@ -131,6 +249,33 @@ impl From<&InstallCacheJson> for InstallCache {
                 dirty.insert(pkg_id);
             }
         }
+        let mut rolled_back = EnumSet::new();
+        for name in &value.rolled_back {
+            let Some(pkg_id) = PkgId::from_str(name) else {
+                continue;
+            };
+            if pkgs.contains(pkg_id) {
+                rolled_back.insert(pkg_id);
+            }
+        }
+        let mut held = EnumSet::new();
+        for name in &value.held {
+            let Some(pkg_id) = PkgId::from_str(name) else {
+                continue;
+            };
+            if pkgs.contains(pkg_id) {
+                held.insert(pkg_id);
+            }
+        }
+        let mut config_hashes: EnumMap<PkgId, Option<String>> = EnumMap::default();
+        for (name, hash) in &value.config_hashes {
+            let Some(pkg_id) = PkgId::from_str(name) else {
+                continue;
+            };
+            if pkgs.contains(pkg_id) {
+                config_hashes[pkg_id] = Some(hash.clone());
+            }
+        }
         let mut bins: EnumMap<BinId, Option<PkgId>> = EnumMap::default();
         for (bin, pkg) in &value.bins {
             let Some(bin_id) = BinId::from_str(bin) else {
@@ -148,7 +293,16 @@ impl From<&InstallCacheJson> for InstallCache {
             }
             bins[bin_id] = Some(pkg_id);
         }
-        Self { pkgs, dirty, bins }
+        Self {
+            pkgs,
+            explicit,
+            dirty,
+            rolled_back,
+            held,
+            config_hashes,
+            requested: EnumSet::new(),
+            bins,
+        }
     }
 }
 impl From<InstallCacheJson> for InstallCache {
@@ -161,13 +315,29 @@ impl From<InstallCacheJson> for InstallCache {
 impl From<&InstallCache> for InstallCacheJson {
     fn from(value: &InstallCache) -> Self {
         let pkgs = value.pkgs.iter().map(|x| x.to_string()).collect();
+        let explicit = Some(value.explicit.iter().map(|x| x.to_string()).collect());
         let dirty = value.dirty.iter().map(|x| x.to_string()).collect();
+        let rolled_back = value.rolled_back.iter().map(|x| x.to_string()).collect();
+        let held = value.held.iter().map(|x| x.to_string()).collect();
+        let config_hashes = value
+            .config_hashes
+            .iter()
+            .filter_map(|(k, v)| Some((k.to_string(), v.clone()?)))
+            .collect();
         let bins = value
             .bins
             .iter()
             .filter_map(|(k, v)| Some((k.to_string(), v.as_ref().copied()?.to_string())))
             .collect();
-        Self { pkgs, dirty, bins }
+        Self {
+            pkgs,
+            explicit,
+            dirty,
+            rolled_back,
+            held,
+            config_hashes,
+            bins,
+        }
     }
 }
 impl From<InstallCache> for InstallCacheJson {
@@ -181,9 +351,84 @@ impl From<InstallCache> for InstallCacheJson {
 struct InstallCacheJson {
     /// List of packages installed
     pub pkgs: Vec<String>,
+    /// List of packages installed explicitly. `None` if not recorded
+    /// by older versions
+    #[serde(default)]
+    pub explicit: Option<Vec<String>>,
     /// List of packages with dirtied (edited) config
     #[serde(default)]
     pub dirty: Vec<String>,
+    /// List of packages rolled back to the previous installation
+    #[serde(default)]
+    pub rolled_back: Vec<String>,
+    /// List of packages held at the installed version
+    #[serde(default)]
+    pub held: Vec<String>,
+    /// Packages mapping to the hash of the config file when last configured
+    #[serde(default)]
+    pub config_hashes: BTreeMap<String, String>,
     /// Binaries available mapping to the package that provides it
     pub bins: BTreeMap<String, String>,
 }
+
+/// Check if either package declares the other in `conflicts!`
+fn is_declared_conflict(a: PkgId, b: PkgId) -> bool {
+    a.package().conflicts().contains(b) || b.package().conflicts().contains(a)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rolled_back() {
+        let mut installed = InstallCache::default();
+        installed.pkgs.insert(PkgId::Python);
+        installed.set_rolled_back(PkgId::Python, true);
+        assert!(installed.is_frozen(PkgId::Python));
+
+        // persisted, but only for installed packages
+        let mut json = InstallCacheJson::from(&installed);
+        json.rolled_back.push("cargo-binstall".to_string());
+        let mut installed = InstallCache::from(json);
+        assert_eq!(installed.rolled_back, EnumSet::only(PkgId::Python));
+
+        // cleared when synced explicitly
+        installed.set_rolled_back(PkgId::Python, false);
+        assert!(!installed.is_frozen(PkgId::Python));
+        let installed2 = InstallCache::from(InstallCacheJson::from(&installed));
+        assert!(installed2.rolled_back.is_empty());
+
+        // cleared when removed
+        installed.set_rolled_back(PkgId::Python, true);
+        installed.remove(PkgId::Python);
+        assert!(!installed.is_rolled_back(PkgId::Python));
+    }
+
+    #[test]
+    fn test_check_conflicts() {
+        let mut installed = InstallCache::default();
+        installed.add(PkgId::SystemPython).unwrap();
+        assert!(
+            installed
+                .check_conflicts(PkgId::SystemPython.into())
+                .is_ok()
+        );
+        assert!(
+            installed
+                .check_conflicts(PkgId::CargoBinstall.into())
+                .is_ok()
+        );
+        // installed packages in the set do not skip checking the others
+        assert!(
+            installed
+                .check_conflicts(PkgId::SystemPython | PkgId::Python)
+                .is_err()
+        );
+        assert!(
+            InstallCache::default()
+                .check_conflicts(PkgId::SystemPython | PkgId::Python)
+                .is_err()
+        );
+    }
+}
@@ -1,20 +1,49 @@
 use cu::pre::*;
 use enum_map::EnumMap;
 use enumset::EnumSet;
-use registry::{BinId, PkgId};
+use itertools::Itertools as _;
+use registry::{BinId, GroupId, PkgId};
+
+use crate::config::ProviderPolicy;
 
 mod install_cache;
 pub use install_cache::InstallCache;
+mod sync_journal;
+pub use sync_journal::{SyncJournal, check_interrupted_sync};
 
+/// Parse package names. `@<group>` expands to the packages in the group
 pub fn parse_pkgs(idents: &[String]) -> cu::Result<EnumSet<PkgId>> {
     let mut pkgs = EnumSet::new();
     for ident in idents {
+        if let Some(group) = ident.strip_prefix('@') {
+            pkgs.insert_all(parse_group(group)?);
+            continue;
+        }
         let pkg = cu::check!(PkgId::from_str(ident), "cannot find package '{ident}'")?;
         pkgs.insert(pkg);
     }
     Ok(pkgs)
 }
 
+/// Get the packages in the group (name without `@`) that are enabled on the current platform
+pub fn parse_group(name: &str) -> cu::Result<EnumSet<PkgId>> {
+    let Some(group) = GroupId::from_str(name) else {
+        let groups = EnumSet::<GroupId>::all()
+            .iter()
+            .map(|x| format!("@{x}"))
+            .join(", ");
+        cu::hint!("available groups: [ {groups} ]");
+        cu::bail!("cannot find package group '@{name}'");
+    };
+    let pkgs = group.packages();
+    if pkgs.is_empty() {
+        cu::warn!("package group '@{group}' has no packages on the current platform");
+    } else {
+        cu::debug!("expanded package group '@{group}': {pkgs}");
+    }
+    Ok(pkgs)
+}
+
 pub fn build_remove_graph(
     pkgs: EnumSet<PkgId>,
     installed: &InstallCache,
@@ -34,7 +63,12 @@ pub fn build_remove_graph(
             temp_installed.remove(pkg_id);
             // build a sync graph for the remaining packages
             let sync_graph = cu::check!(
-                build_sync_graph(temp_installed.pkgs, &temp_installed, provider_selection),
+                build_sync_graph(
+                    temp_installed.pkgs,
+                    &temp_installed,
+                    provider_selection,
+                    OptionalDeps::Skip
+                ),
                 "failed to resolve sync graph when removing '{pkg_id}'"
             )?;
             // if the new sync graph contains the package to remove,
@@ -52,6 +86,7 @@ pub fn build_remove_graph(
                 .map(|x| x.to_string())
                 .collect::<Vec<_>>()
                 .join(", ");
+            cu::hint!("run `shaft why <package>` to see which packages depend on it");
             cu::bail!(
                 "cannot remove the following packages because other packages depend on them: [ {pkgs_string} ]"
             );
@@ -61,11 +96,43 @@ pub fn build_remove_graph(
     Ok(out)
 }
 
+/// How [`build_sync_graph`] handles the optional dependencies of the packages
+/// that are not installed
+pub enum OptionalDeps<'a> {
+    /// Leave them out
+    Skip,
+    /// Leave them out, and hint that they can be installed
+    Hint,
+    /// Ask whether to install them. The accepted ones are added to the graph and to the set
+    Offer(&'a mut EnumSet<PkgId>),
+}
+
 pub fn build_sync_graph(
     pkgs: EnumSet<PkgId>,
     installed: &InstallCache,
     provider_selection: &mut EnumMap<BinId, Option<PkgId>>,
+    optional: OptionalDeps<'_>,
 ) -> cu::Result<Vec<PkgId>> {
+    let pkgs = match optional {
+        OptionalDeps::Skip => pkgs,
+        OptionalDeps::Hint => {
+            for (opt, dependents) in
+                &missing_optional_dependencies(pkgs, installed, &optional_dependencies)
+            {
+                if !dependents.is_empty() {
+                    cu::hint!(
+                        "'{opt}' is optional for [ {} ]",
+                        dependents.iter().join(", ")
+                    );
+                }
+            }
+            pkgs
+        }
+        OptionalDeps::Offer(accepted) => {
+            accepted.insert_all(offer_optional_dependencies(pkgs, installed)?);
+            pkgs | *accepted
+        }
+    };
     cu::debug!("building sync graph for {pkgs}");
     let mut sync_pkgs = EnumSet::new();
     for pkg_id in pkgs {
@@ -113,7 +180,7 @@ pub fn resolve_config_pkgs(
     loop {
         let len_before = sync_pkgs.len();
         for pkg_id in installed.pkgs {
-            let cfg_deps = pkg_id.package().config_dependencies();
+            let cfg_deps = config_dependencies(pkg_id);
             for cfg_id in cfg_deps {
                 if seed_pkgs.contains(cfg_id) || sync_pkgs.contains(cfg_id) {
                     sync_pkgs.insert(pkg_id);
@@ -159,7 +226,7 @@ pub fn resolve_sync_order(
                 }
             }
             // check config deps
-            let cfg_deps = pkg_id.package().config_dependencies();
+            let cfg_deps = config_dependencies(pkg_id);
             for cfg_id in cfg_deps {
                 if remaining.contains(cfg_id) {
                     // not all cfg deps added
@@ -169,13 +236,28 @@ pub fn resolve_sync_order(
             next_to_add.insert(pkg_id);
         }
         if next_to_add.is_empty() {
-            let pkgs_string = remaining
-                .iter()
-                .map(|x| x.to_string())
-                .collect::<Vec<_>>()
-                .join(", ");
+            let cycles = find_cycles(remaining, &|pkg| {
+                sync_dependencies(pkg, remaining, bin_providers)
+            });
+            let mut in_cycles = EnumSet::new();
+            for cycle in &cycles {
+                in_cycles.extend(cycle.iter().map(|x| x.0));
+                cu::error!("dependency cycle: {}", format_cycle(cycle));
+            }
+            let blocked = remaining.difference(in_cycles);
+            if !blocked.is_empty() {
+                let pkgs_string = blocked.iter().join(", ");
+                cu::hint!("blocked by the cycles: [ {pkgs_string} ]");
+            }
+            if cycles.is_empty() {
+                let pkgs_string = remaining.iter().join(", ");
+                cu::bail!(
+                    "the order of the remaining packages cannot be determined: [ {pkgs_string} ]"
+                );
+            }
             cu::bail!(
-                "the order of the remaining packages cannot be determined: [ {pkgs_string} ]"
+                "the order of the packages cannot be determined because of {} dependency cycle(s)",
+                cycles.len()
             );
         }
         out.extend(next_to_add);
@@ -184,6 +266,213 @@ pub fn resolve_sync_order(
     Ok(out)
 }
 
+/// Group the packages in the sync order into waves. Packages in the same wave
+/// do not depend on each other, so they can be synced at the same time,
+/// after all packages in the previous waves are synced
+pub fn sync_waves(
+    graph: &[PkgId],
+    bin_providers: &EnumMap<BinId, Option<PkgId>>,
+) -> Vec<Vec<PkgId>> {
+    let pkgs: EnumSet<PkgId> = graph.iter().copied().collect();
+    let mut wave_of: EnumMap<PkgId, usize> = EnumMap::default();
+    let mut waves: Vec<Vec<PkgId>> = vec![];
+    for pkg in graph.iter().copied() {
+        // core-pseudo is always synced first by itself
+        let wave = if pkg == PkgId::Core {
+            0
+        } else {
+            sync_dependencies(pkg, pkgs, bin_providers)
+                .into_iter()
+                .map(|(dep, _)| wave_of[dep] + 1)
+                .max()
+                .unwrap_or(1)
+                .max(1)
+        };
+        wave_of[pkg] = wave;
+        if waves.len() <= wave {
+            waves.resize_with(wave + 1, Vec::new);
+        }
+        waves[wave].push(pkg);
+    }
+    waves.retain(|x| !x.is_empty());
+    waves
+}
+
+/// Kind of the dependency between packages in the sync graph
+#[derive(Debug, Clone, Copy)]
+enum DepEdge {
+    /// Depends on the binary provided by the package
+    Bin(BinId),
+    /// Depends on the config of the package
+    Config,
+}
+impl std::fmt::Display for DepEdge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DepEdge::Bin(bin) => write!(f, "bin:{bin}"),
+            DepEdge::Config => write!(f, "config"),
+        }
+    }
+}
+
+/// Get the config dependencies of the package, including the optional dependencies,
+/// since they are configured before the package when installed
+pub fn config_dependencies(pkg: PkgId) -> EnumSet<PkgId> {
+    let package = pkg.package();
+    package.config_dependencies() | package.optional_dependencies()
+}
+
+fn optional_dependencies(pkg: PkgId) -> EnumSet<PkgId> {
+    pkg.package().optional_dependencies()
+}
+
+/// Get the dependencies of the package that are in `pkgs`, with the kind of the dependency
+fn sync_dependencies(
+    pkg: PkgId,
+    pkgs: EnumSet<PkgId>,
+    bin_providers: &EnumMap<BinId, Option<PkgId>>,
+) -> Vec<(PkgId, DepEdge)> {
+    let mut out = vec![];
+    for bin_id in pkg.package().binary_dependencies() {
+        if let Some(provider) = bin_providers[bin_id]
+            && pkgs.contains(provider)
+        {
+            out.push((provider, DepEdge::Bin(bin_id)));
+        }
+    }
+    for cfg_id in config_dependencies(pkg) {
+        if pkgs.contains(cfg_id) {
+            out.push((cfg_id, DepEdge::Config));
+        }
+    }
+    out
+}
+
+/// Find the dependency cycles among the packages, one for each strongly connected component.
+/// `dependencies` returns the dependencies of a package, see [`sync_dependencies`].
+///
+/// Each cycle is a list of packages with the edge to the next package,
+/// where the last package goes back to the first
+fn find_cycles(
+    pkgs: EnumSet<PkgId>,
+    dependencies: &dyn Fn(PkgId) -> Vec<(PkgId, DepEdge)>,
+) -> Vec<Vec<(PkgId, DepEdge)>> {
+    let mut tarjan = Tarjan {
+        pkgs,
+        dependencies,
+        next_index: 0,
+        index: EnumMap::default(),
+        lowlink: EnumMap::default(),
+        stack: vec![],
+        on_stack: EnumSet::new(),
+        components: vec![],
+    };
+    for pkg in pkgs {
+        if tarjan.index[pkg].is_none() {
+            tarjan.visit(pkg);
+        }
+    }
+    let mut cycles = vec![];
+    for component in tarjan.components {
+        // BFS from the first package back to itself to find the shortest cycle
+        let Some(start) = component.iter().next() else {
+            continue;
+        };
+        let mut parent: EnumMap<PkgId, Option<(PkgId, DepEdge)>> = EnumMap::default();
+        let mut visited = EnumSet::new();
+        let mut queue = std::collections::VecDeque::from([start]);
+        let mut last = None;
+        'bfs: while let Some(pkg) = queue.pop_front() {
+            for (dep, edge) in dependencies(pkg) {
+                if !component.contains(dep) {
+                    continue;
+                }
+                if dep == start {
+                    last = Some((pkg, edge));
+                    break 'bfs;
+                }
+                if visited.insert(dep) {
+                    parent[dep] = Some((pkg, edge));
+                    queue.push_back(dep);
+                }
+            }
+        }
+        // single package without a self loop is not a cycle
+        let Some((mut pkg, edge)) = last else {
+            continue;
+        };
+        let mut cycle = vec![(pkg, edge)];
+        while pkg != start {
+            let Some((prev, edge)) = parent[pkg] else {
+                break;
+            };
+            cycle.push((prev, edge));
+            pkg = prev;
+        }
+        cycle.reverse();
+        cycles.push(cycle);
+    }
+    cycles
+}
+
+/// Format the cycle as a chain, for example `a --[bin:b]--> b --[config]--> a`
+fn format_cycle(cycle: &[(PkgId, DepEdge)]) -> String {
+    let mut chain = cycle[0].0.to_string();
+    for (i, (_, edge)) in cycle.iter().enumerate() {
+        let next = cycle[(i + 1) % cycle.len()].0;
+        chain.push_str(&format!(" --[{edge}]--> {next}"));
+    }
+    chain
+}
+
+/// State for Tarjan's strongly connected components algorithm
+struct Tarjan<'a> {
+    pkgs: EnumSet<PkgId>,
+    dependencies: &'a dyn Fn(PkgId) -> Vec<(PkgId, DepEdge)>,
+    next_index: usize,
+    index: EnumMap<PkgId, Option<usize>>,
+    lowlink: EnumMap<PkgId, usize>,
+    stack: Vec<PkgId>,
+    on_stack: EnumSet<PkgId>,
+    components: Vec<EnumSet<PkgId>>,
+}
+impl Tarjan<'_> {
+    fn visit(&mut self, pkg: PkgId) {
+        self.index[pkg] = Some(self.next_index);
+        self.lowlink[pkg] = self.next_index;
+        self.next_index += 1;
+        self.stack.push(pkg);
+        self.on_stack.insert(pkg);
+        for (dep, _) in (self.dependencies)(pkg) {
+            if !self.pkgs.contains(dep) {
+                continue;
+            }
+            match self.index[dep] {
+                None => {
+                    self.visit(dep);
+                    self.lowlink[pkg] = self.lowlink[pkg].min(self.lowlink[dep]);
+                }
+                Some(dep_index) if self.on_stack.contains(dep) => {
+                    self.lowlink[pkg] = self.lowlink[pkg].min(dep_index);
+                }
+                Some(_) => {}
+            }
+        }
+        if Some(self.lowlink[pkg]) != self.index[pkg] {
+            return;
+        }
+        let mut component = EnumSet::new();
+        while let Some(x) = self.stack.pop() {
+            self.on_stack.remove(x);
+            component.insert(x);
+            if x == pkg {
+                break;
+            }
+        }
+        self.components.push(component);
+    }
+}
+
 #[cu::context("when collecting dependencies for package '{pkg}'")]
 pub fn collect_dependencies(
     pkg: PkgId,
@@ -200,10 +489,10 @@ pub fn collect_dependencies(
     let bin_deps = pkg.package().binary_dependencies();
     cu::debug!("bin_deps for '{pkg}': {bin_deps}");
     for bin_id in bin_deps {
-        let provider = select_provider(provider_selection, bin_id, installed)?;
+        let provider = select_dependency_provider(pkg, bin_id, provider_selection, installed)?;
         collect_dependencies(provider, installed, out_pkgs, provider_selection)?;
     }
-    let cfg_deps = pkg.package().config_dependencies();
+    let cfg_deps = config_dependencies(pkg);
     for cfg_id in cfg_deps {
         if installed.pkgs.contains(cfg_id) {
             collect_dependencies(cfg_id, installed, out_pkgs, provider_selection)?;
@@ -213,6 +502,237 @@ pub fn collect_dependencies(
     Ok(())
 }
 
+/// Find optional dependencies of `pkgs` that are not installed and not in `pkgs`,
+/// mapped to the packages in `pkgs` they enhance. Optional dependencies that
+/// cannot be installed (because of conflicts or platform) are skipped.
+///
+/// `optional_dependencies` returns the optional dependencies of a package
+fn missing_optional_dependencies(
+    pkgs: EnumSet<PkgId>,
+    installed: &InstallCache,
+    optional_dependencies: &dyn Fn(PkgId) -> EnumSet<PkgId>,
+) -> EnumMap<PkgId, EnumSet<PkgId>> {
+    let mut out: EnumMap<PkgId, EnumSet<PkgId>> = EnumMap::default();
+    for pkg in pkgs {
+        for opt in optional_dependencies(pkg) {
+            if installed.pkgs.contains(opt) || pkgs.contains(opt) {
+                continue;
+            }
+            if !opt.package().enabled() {
+                cu::debug!("optional dependency '{opt}' of '{pkg}' is not enabled");
+                continue;
+            }
+            let new_pkgs = (pkgs | opt).difference(installed.pkgs);
+            if let Err(e) = installed.check_conflicts(new_pkgs) {
+                cu::debug!("optional dependency '{opt}' of '{pkg}' cannot be installed: {e:?}");
+                continue;
+            }
+            out[opt].insert(pkg);
+        }
+    }
+    out
+}
+
+/// Ask whether to install the missing optional dependencies of `pkgs`, and return
+/// the ones accepted. Nothing is installed when running unattended, since optional
+/// dependencies are never required
+fn offer_optional_dependencies(
+    pkgs: EnumSet<PkgId>,
+    installed: &InstallCache,
+) -> cu::Result<EnumSet<PkgId>> {
+    let mut accepted = EnumSet::new();
+    for (opt, dependents) in &missing_optional_dependencies(pkgs, installed, &optional_dependencies)
+    {
+        if dependents.is_empty() {
+            continue;
+        }
+        let dependents = dependents.iter().join(", ");
+        if crate::interactive::is_unattended() {
+            cu::hint!(
+                "'{opt}' is optional for [ {dependents} ], run `shaft sync {opt}` to install it"
+            );
+            continue;
+        }
+        if cu::yesno!("install optional package '{opt}', which enhances [ {dependents} ]?")? {
+            accepted.insert(opt);
+        }
+    }
+    Ok(accepted)
+}
+
+/// Find installed packages that are not installed explicitly, and are not
+/// needed by any explicitly installed package, assuming `removing` are removed
+pub fn find_orphans(installed: &InstallCache, removing: EnumSet<PkgId>) -> EnumSet<PkgId> {
+    let mut needed = EnumSet::new();
+    let mut stack = installed
+        .explicit
+        .difference(removing)
+        .iter()
+        .collect::<Vec<_>>();
+    stack.push(PkgId::Core);
+    while let Some(pkg) = stack.pop() {
+        if !needed.insert(pkg) {
+            continue;
+        }
+        for bin in pkg.package().binary_dependencies() {
+            if let Some(provider) = installed.bins[bin] {
+                stack.push(provider);
+            }
+        }
+    }
+    installed.pkgs.difference(needed).difference(removing)
+}
+
+/// Fill in the provider selection from the config, for binaries not already selected
+/// and not already provided by an installed package.
+///
+/// Binaries that are not selected by the config are selected with the policy in the config
+#[cu::context("failed to select providers from config")]
+pub fn preselect_providers(
+    provider_selection: &mut EnumMap<BinId, Option<PkgId>>,
+    installed: &InstallCache,
+) -> cu::Result<()> {
+    let config = crate::config::load_config()?.providers;
+    for (bin, pkg) in &config.select {
+        let Some(bin_id) = BinId::from_str(bin) else {
+            cu::warn!("ignoring provider for unknown binary '{bin}' in config");
+            continue;
+        };
+        let Some(pkg_id) = PkgId::from_str(pkg) else {
+            cu::warn!("ignoring unknown package '{pkg}' selected for '{bin_id}' in config");
+            continue;
+        };
+        if !bin_id.providers().contains(pkg_id) {
+            cu::warn!(
+                "ignoring package '{pkg_id}' selected for '{bin_id}' in config, as it does not provide the binary"
+            );
+            continue;
+        }
+        if let Some(installed_pkg) = installed.bins[bin_id] {
+            // keep using the installed provider, which would conflict otherwise
+            if installed_pkg != pkg_id {
+                cu::debug!(
+                    "not selecting '{pkg_id}' for '{bin_id}' from config, as '{installed_pkg}' is installed"
+                );
+            }
+            continue;
+        }
+        if provider_selection[bin_id].is_none() {
+            cu::debug!("config selected provider for '{bin_id}': '{pkg_id}'");
+            provider_selection[bin_id] = Some(pkg_id);
+        }
+    }
+    let prefer_system = match config.policy {
+        ProviderPolicy::Prompt => return Ok(()),
+        ProviderPolicy::PreferSystem => true,
+        ProviderPolicy::PreferShaft => false,
+    };
+    for bin_id in EnumSet::<BinId>::all() {
+        if provider_selection[bin_id].is_some() || installed.bins[bin_id].is_some() {
+            continue;
+        }
+        let providers = bin_id.providers();
+        if providers.len() < 2 {
+            continue;
+        }
+        let preferred = providers
+            .iter()
+            .find(|x| x.to_str().starts_with("system-") == prefer_system);
+        if let Some(pkg_id) = preferred {
+            cu::debug!("policy selected provider for '{bin_id}': '{pkg_id}'");
+            provider_selection[bin_id] = Some(pkg_id);
+        }
+    }
+    Ok(())
+}
+
+/// Select the provider for a binary dependency of `pkg`, making sure the provider
+/// satisfies the minimum version the package requires for the binary.
+///
+/// If the selected provider is too old and not installed yet, another provider
+/// that satisfies the version is selected instead
+#[cu::context("failed to select provider for binary '{bin_id}' required by '{pkg}'")]
+pub fn select_dependency_provider(
+    pkg: PkgId,
+    bin_id: BinId,
+    provider_selection: &mut EnumMap<BinId, Option<PkgId>>,
+    installed: &InstallCache,
+) -> cu::Result<PkgId> {
+    let provider = select_provider(provider_selection, bin_id, installed)?;
+    let Some(min_version) = pkg.package().binary_dependency_version(bin_id) else {
+        return Ok(provider);
+    };
+    let Some(version) = outdated_provider_version(pkg, bin_id, provider, min_version, installed)
+    else {
+        return Ok(provider);
+    };
+    let others = bin_id
+        .providers()
+        .iter()
+        .filter(|x| *x != provider)
+        .collect::<Vec<_>>();
+    // alternatives are only selected if they are known to be new enough
+    let new_provider = others.iter().copied().find(|x| {
+        !keeps_version(*x, installed)
+            || bin_id
+                .version_from(*x)
+                .is_some_and(|v| registry::satisfies_min_version(&v, min_version))
+    });
+    let new_provider = match new_provider {
+        Some(x) if !installed.pkgs.contains(provider) => x,
+        _ => {
+            if installed.is_frozen(provider) {
+                cu::hint!("run `shaft sync {provider}` to upgrade it");
+            } else if !others.is_empty() {
+                cu::hint!(
+                    "upgrade '{bin_id}' in the system, or remove '{provider}' and install one of: [ {} ]",
+                    others.iter().map(|x| x.to_str()).join(", ")
+                );
+            }
+            cu::bail!(
+                "'{pkg}' requires '{bin_id}' {min_version} or newer, but '{provider}' provides {version}"
+            );
+        }
+    };
+    cu::warn!(
+        "'{pkg}' requires '{bin_id}' {min_version} or newer, but '{provider}' provides {version}; selecting '{new_provider}' instead"
+    );
+    provider_selection[bin_id] = Some(new_provider);
+    Ok(new_provider)
+}
+
+/// If the binaries of the provider stay at their current version when syncing.
+///
+/// Packages managed by shaft are synced to the version in the registry,
+/// unless they are kept at the installed version
+fn keeps_version(provider: PkgId, installed: &InstallCache) -> bool {
+    provider.to_str().starts_with("system-") || installed.is_frozen(provider)
+}
+
+/// Get the version of `bin_id` provided by `provider` if it's older than `min_version`
+fn outdated_provider_version(
+    pkg: PkgId,
+    bin_id: BinId,
+    provider: PkgId,
+    min_version: &str,
+    installed: &InstallCache,
+) -> Option<String> {
+    if !keeps_version(provider, installed) {
+        return None;
+    }
+    let Some(version) = bin_id.version_from(provider) else {
+        cu::warn!(
+            "cannot determine the version of '{bin_id}' from '{provider}', '{pkg}' requires {min_version} or newer"
+        );
+        return None;
+    };
+    if registry::satisfies_min_version(&version, min_version) {
+        cu::debug!("'{bin_id}' {version} from '{provider}' satisfies '{pkg}' (>= {min_version})");
+        return None;
+    }
+    Some(version)
+}
+
 #[cu::context("failed to select provider for binary '{bin_id}'")]
 pub fn select_provider(
     provider_selection: &mut EnumMap<BinId, Option<PkgId>>,
@@ -245,6 +765,14 @@ pub fn select_provider(
     }
 
     // prompt for a provider
+    let provider_names = providers.iter().map(|x| x.to_str()).collect::<Vec<_>>();
+    crate::interactive::ensure_input(
+        &format!("select a provider for binary '{bin_id}'"),
+        &format!(
+            "pass `--provider {bin_id}=<package>`, or set `{bin_id} = \"<package>\"` or the policy in the [providers] section of the core config; available: [ {} ]",
+            provider_names.join(", ")
+        ),
+    )?;
     let mut prompt = String::new();
     let _ = writeln!(prompt, "please select a provider for binary '{bin_id}':");
     let pkg_width = providers
@@ -289,3 +817,224 @@ pub fn select_provider(
     cu::debug!("user selected provider for '{bin_id}': '{pkg_id}'");
     Ok(pkg_id)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_missing_optional_dependencies() {
+        let mut installed = InstallCache::default();
+        installed.add(PkgId::Starship).unwrap();
+        installed.add(PkgId::SystemPython).unwrap();
+        let optional = |pkg| match pkg {
+            PkgId::Starship => PkgId::Python | PkgId::CargoBinstall,
+            _ => EnumSet::new(),
+        };
+        let missing =
+            missing_optional_dependencies(EnumSet::only(PkgId::Starship), &installed, &optional);
+        // python conflicts with the installed system-python
+        assert!(missing[PkgId::Python].is_empty());
+        assert_eq!(
+            missing[PkgId::CargoBinstall],
+            EnumSet::only(PkgId::Starship)
+        );
+        // not missing if already being synced
+        let missing = missing_optional_dependencies(
+            PkgId::Starship | PkgId::CargoBinstall,
+            &installed,
+            &optional,
+        );
+        assert!(missing.values().all(|x| x.is_empty()));
+    }
+
+    #[test]
+    fn test_parse_pkgs() {
+        let pkgs = parse_pkgs(&["@web".to_string(), "python".to_string()]).unwrap();
+        assert_eq!(pkgs, GroupId::Web.packages() | PkgId::Python);
+        let pkgs = parse_pkgs(&["@c-dev".to_string(), "@editor".to_string()]).unwrap();
+        assert_eq!(pkgs, GroupId::CDev.packages() | GroupId::Editor.packages());
+        // only packages enabled on the current platform
+        assert!(pkgs.iter().all(|x| x.package().enabled()));
+        assert!(parse_pkgs(&["@python".to_string()]).is_err());
+        assert!(parse_pkgs(&["web".to_string()]).is_err());
+        assert!(parse_pkgs(&[]).unwrap().is_empty());
+    }
+
+    /// python and starship installed explicitly, both depend on cargo-binstall
+    fn installed_fixture() -> InstallCache {
+        let mut installed = InstallCache::default();
+        for pkg in [PkgId::CargoBinstall, PkgId::Python, PkgId::Starship] {
+            installed.pkgs.insert(pkg);
+            for bin in pkg.package().binaries() {
+                installed.bins[bin] = Some(pkg);
+            }
+        }
+        installed.explicit = PkgId::Python | PkgId::Starship;
+        installed
+    }
+
+    #[test]
+    fn test_find_orphans() {
+        let mut installed = installed_fixture();
+        assert!(find_orphans(&installed, EnumSet::new()).is_empty());
+        // still needed by starship
+        assert!(find_orphans(&installed, EnumSet::only(PkgId::Python)).is_empty());
+        assert_eq!(
+            find_orphans(&installed, PkgId::Python | PkgId::Starship),
+            EnumSet::only(PkgId::CargoBinstall)
+        );
+
+        // installed as a dependency, and nothing needs it anymore
+        installed.explicit.remove(PkgId::Starship);
+        assert_eq!(
+            find_orphans(&installed, EnumSet::new()),
+            EnumSet::only(PkgId::Starship)
+        );
+        // explicitly installed packages are never orphans
+        installed.explicit.insert(PkgId::CargoBinstall);
+        assert_eq!(
+            find_orphans(&installed, PkgId::Python | PkgId::Starship),
+            EnumSet::empty()
+        );
+    }
+
+    /// Find cycles with the dependencies given as `(pkg, dep, edge)`
+    fn find_cycles_in(
+        pkgs: EnumSet<PkgId>,
+        deps: &[(PkgId, PkgId, DepEdge)],
+    ) -> Vec<Vec<(PkgId, DepEdge)>> {
+        find_cycles(pkgs, &|pkg| {
+            deps.iter()
+                .filter(|x| x.0 == pkg)
+                .map(|x| (x.1, x.2))
+                .collect()
+        })
+    }
+
+    #[test]
+    fn test_find_cycles_self_loop() {
+        let cycles = find_cycles_in(
+            PkgId::Python | PkgId::Starship,
+            &[(PkgId::Python, PkgId::Python, DepEdge::Config)],
+        );
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(format_cycle(&cycles[0]), "python --[config]--> python");
+    }
+
+    #[test]
+    fn test_find_cycles_two_nodes() {
+        let bin = BinId::CargoBinstall;
+        let cycles = find_cycles_in(
+            PkgId::Python | PkgId::CargoBinstall | PkgId::Starship,
+            &[
+                (PkgId::Python, PkgId::CargoBinstall, DepEdge::Bin(bin)),
+                (
+                    PkgId::CargoBinstall,
+                    PkgId::Python,
+                    DepEdge::Bin(BinId::Python),
+                ),
+                // not part of the cycle
+                (PkgId::Starship, PkgId::CargoBinstall, DepEdge::Bin(bin)),
+            ],
+        );
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(
+            format_cycle(&cycles[0]),
+            "cargo-binstall --[bin:python]--> python --[bin:cargo-binstall]--> cargo-binstall"
+        );
+    }
+
+    #[test]
+    fn test_find_cycles_mixed_edges() {
+        let cycles = find_cycles_in(
+            PkgId::Python | PkgId::CargoBinstall | PkgId::Starship,
+            &[
+                (
+                    PkgId::Starship,
+                    PkgId::CargoBinstall,
+                    DepEdge::Bin(BinId::CargoBinstall),
+                ),
+                (
+                    PkgId::CargoBinstall,
+                    PkgId::Python,
+                    DepEdge::Bin(BinId::Python),
+                ),
+                (PkgId::Python, PkgId::Starship, DepEdge::Config),
+            ],
+        );
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(
+            format_cycle(&cycles[0]),
+            "cargo-binstall --[bin:python]--> python --[config]--> starship --[bin:cargo-binstall]--> cargo-binstall"
+        );
+    }
+
+    #[test]
+    fn test_find_cycles_acyclic() {
+        let cycles = find_cycles_in(
+            PkgId::Python | PkgId::CargoBinstall | PkgId::Starship,
+            &[
+                (
+                    PkgId::Python,
+                    PkgId::CargoBinstall,
+                    DepEdge::Bin(BinId::CargoBinstall),
+                ),
+                (
+                    PkgId::Starship,
+                    PkgId::CargoBinstall,
+                    DepEdge::Bin(BinId::CargoBinstall),
+                ),
+                (PkgId::Starship, PkgId::Python, DepEdge::Config),
+                // outside of the packages being checked
+                (PkgId::CargoBinstall, PkgId::SystemGit, DepEdge::Config),
+                (PkgId::SystemGit, PkgId::Python, DepEdge::Config),
+            ],
+        );
+        assert!(cycles.is_empty());
+    }
+
+    #[test]
+    fn test_remove_recursive() {
+        let installed = installed_fixture();
+        let pkgs = PkgId::Python | PkgId::Starship;
+        let pkgs = pkgs | find_orphans(&installed, pkgs);
+        let graph = build_remove_graph(pkgs, &installed, &mut Default::default()).unwrap();
+        assert_eq!(graph.len(), 3);
+        // dependencies are removed after the packages that depend on them
+        assert_eq!(graph.last(), Some(&PkgId::CargoBinstall));
+    }
+
+    #[test]
+    fn test_sync_waves() {
+        let graph = vec![
+            PkgId::Core,
+            PkgId::CargoBinstall,
+            PkgId::SystemGit,
+            PkgId::Python,
+            PkgId::Starship,
+        ];
+        let mut providers = EnumMap::default();
+        providers[BinId::CargoBinstall] = Some(PkgId::CargoBinstall);
+        assert_eq!(
+            sync_waves(&graph, &providers),
+            vec![
+                vec![PkgId::Core],
+                vec![PkgId::CargoBinstall, PkgId::SystemGit],
+                vec![PkgId::Python, PkgId::Starship],
+            ]
+        );
+        // dependencies not in the graph do not delay the package
+        let graph = &graph[2..];
+        assert_eq!(
+            sync_waves(graph, &providers),
+            vec![vec![PkgId::SystemGit, PkgId::Python, PkgId::Starship]]
+        );
+        // the package is not a dependency if it's not the selected provider
+        let graph = vec![PkgId::CargoBinstall, PkgId::Python];
+        assert_eq!(
+            sync_waves(&graph, &EnumMap::default()),
+            vec![vec![PkgId::CargoBinstall, PkgId::Python]]
+        );
+    }
+}
@@ -0,0 +1,425 @@
+use std::collections::BTreeMap;
+
+use corelib::{ItemMgr, hmgr};
+use cu::pre::*;
+use enum_map::EnumMap;
+use enumset::EnumSet;
+use registry::{BinId, Context, PkgId, Stage};
+
+/// On-disk record of an in-flight sync, so it can be recovered
+/// if the process dies in the middle of syncing a package
+#[derive(Debug, Default, Clone)]
+pub struct SyncJournal {
+    /// Packages requested to sync
+    pub pkgs: EnumSet<PkgId>,
+    /// Packages requested explicitly by the user
+    pub requested: EnumSet<PkgId>,
+    /// Selected providers for binaries
+    pub providers: EnumMap<BinId, Option<PkgId>>,
+    /// The planned sync order
+    pub graph: Vec<PkgId>,
+    /// Packages in the graph that are done syncing
+    pub done: EnumSet<PkgId>,
+    /// Packages being synced. Multiple packages can be synced at the same time
+    pub in_flight: EnumMap<PkgId, Option<InFlight>>,
+}
+
+/// State of a package being synced
+#[derive(Debug, Clone, Copy)]
+pub struct InFlight {
+    /// The stage the package is in
+    pub stage: Stage,
+    /// If a backup of the package is taken and not cleared yet.
+    /// The backup needs to be restored if the package did not finish syncing
+    pub backup: bool,
+}
+
+impl SyncJournal {
+    pub fn new(
+        pkgs: EnumSet<PkgId>,
+        requested: EnumSet<PkgId>,
+        providers: EnumMap<BinId, Option<PkgId>>,
+        graph: Vec<PkgId>,
+    ) -> Self {
+        Self {
+            pkgs,
+            requested,
+            providers,
+            graph,
+            ..Default::default()
+        }
+    }
+
+    /// Load the journal, returns `None` if there is no sync in-flight
+    #[cu::context("failed to load sync journal")]
+    pub fn load() -> cu::Result<Option<Self>> {
+        let path = hmgr::paths::sync_journal_json();
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = cu::fs::read_string(path)?;
+        let journal: SyncJournalJson = json::parse(&content)?;
+        cu::debug!("sync journal loaded: {journal:?}");
+        Ok(Some(journal.into()))
+    }
+
+    #[cu::context("failed to save sync journal")]
+    pub fn save(&self) -> cu::Result<()> {
+        let path = hmgr::paths::sync_journal_json();
+        cu::fs::write_json_pretty(path, &SyncJournalJson::from(self))?;
+        Ok(())
+    }
+
+    /// Delete the journal after the sync is finished
+    #[cu::context("failed to remove sync journal")]
+    pub fn remove() -> cu::Result<()> {
+        cu::fs::remove(hmgr::paths::sync_journal_json())?;
+        Ok(())
+    }
+
+    /// Record the stage of the package being synced
+    pub fn set_stage(&mut self, pkg: PkgId, stage: Stage) -> cu::Result<()> {
+        let backup = self.in_flight[pkg].is_some_and(|x| x.backup);
+        self.in_flight[pkg] = Some(InFlight { stage, backup });
+        self.save()
+    }
+
+    /// Record if a backup of the package is pending restore
+    pub fn set_backup(&mut self, pkg: PkgId, backup: bool) -> cu::Result<()> {
+        if let Some(in_flight) = &mut self.in_flight[pkg] {
+            in_flight.backup = backup;
+        }
+        self.save()
+    }
+
+    /// Record that the package failed to sync.
+    /// The backup was restored (or attempted) by the restore guard
+    pub fn fail(&mut self, pkg: PkgId) -> cu::Result<()> {
+        self.in_flight[pkg] = None;
+        self.save()
+    }
+
+    /// Record that the package is done syncing
+    pub fn finish(&mut self, pkg: PkgId) -> cu::Result<()> {
+        self.done.insert(pkg);
+        self.in_flight[pkg] = None;
+        self.save()
+    }
+
+    /// Restore the backups of the packages that did not finish syncing, if there are any
+    pub fn restore_pending_backups(&mut self) -> cu::Result<()> {
+        for (pkg, in_flight) in self.in_flight {
+            let Some(InFlight {
+                stage,
+                backup: true,
+            }) = in_flight
+            else {
+                continue;
+            };
+            cu::warn!(
+                "restoring the backup of '{pkg}', which was interrupted at the {stage:?} stage"
+            );
+            let mut ctx = Context::new(ItemMgr::default());
+            ctx.pkg = pkg;
+            cu::check!(
+                pkg.package().restore(&ctx),
+                "failed to restore the backup of '{pkg}'"
+            )?;
+            cu::info!("restored '{pkg}'");
+            self.set_backup(pkg, false)?;
+        }
+        Ok(())
+    }
+}
+
+/// Check for a sync that was interrupted, and restore the backup of the package
+/// that did not finish syncing. Returns if there is an interrupted sync.
+///
+/// The packages that were in-flight are cleared from the journal after the backups
+/// are restored, so the interruption is only reported once. They are synced
+/// again on resume since they are not done.
+///
+/// If `dry_run`, the interrupted sync is only reported, and nothing is changed
+pub fn check_interrupted_sync(dry_run: bool) -> cu::Result<bool> {
+    let Some(mut journal) = SyncJournal::load()? else {
+        return Ok(false);
+    };
+    let mut interrupted = false;
+    for (pkg, in_flight) in &journal.in_flight {
+        if let Some(InFlight { stage, .. }) = in_flight {
+            cu::warn!("the last sync was interrupted while syncing '{pkg}' at the {stage:?} stage");
+            interrupted = true;
+        }
+    }
+    if !interrupted {
+        cu::info!("the last sync did not finish");
+        return Ok(true);
+    }
+    if dry_run {
+        cu::info!("dry run: not restoring backups of the interrupted sync");
+        return Ok(true);
+    }
+    journal.restore_pending_backups()?;
+    journal.in_flight = EnumMap::default();
+    journal.save()?;
+    Ok(true)
+}
+
+impl From<SyncJournalJson> for SyncJournal {
+    fn from(value: SyncJournalJson) -> Self {
+        let pkgs = value
+            .pkgs
+            .iter()
+            .filter_map(|x| PkgId::from_str(x))
+            .collect();
+        let requested = value
+            .requested
+            .iter()
+            .filter_map(|x| PkgId::from_str(x))
+            .collect();
+        let mut providers: EnumMap<BinId, Option<PkgId>> = EnumMap::default();
+        for (bin, pkg) in &value.providers {
+            let Some(bin_id) = BinId::from_str(bin) else {
+                continue;
+            };
+            let Some(pkg_id) = PkgId::from_str(pkg) else {
+                continue;
+            };
+            if bin_id.providers().contains(pkg_id) {
+                providers[bin_id] = Some(pkg_id);
+            }
+        }
+        let graph = value
+            .graph
+            .iter()
+            .filter_map(|x| PkgId::from_str(x))
+            .collect();
+        let done = value
+            .done
+            .iter()
+            .filter_map(|x| PkgId::from_str(x))
+            .collect();
+        let mut in_flight: EnumMap<PkgId, Option<InFlight>> = EnumMap::default();
+        for x in &value.in_flight {
+            let Some(pkg_id) = PkgId::from_str(&x.pkg) else {
+                continue;
+            };
+            in_flight[pkg_id] = Some(InFlight {
+                stage: x.stage.into(),
+                backup: x.backup,
+            });
+        }
+        Self {
+            pkgs,
+            requested,
+            providers,
+            graph,
+            done,
+            in_flight,
+        }
+    }
+}
+
+impl From<&SyncJournal> for SyncJournalJson {
+    fn from(value: &SyncJournal) -> Self {
+        let pkgs = value.pkgs.iter().map(|x| x.to_string()).collect();
+        let requested = value.requested.iter().map(|x| x.to_string()).collect();
+        let providers = value
+            .providers
+            .iter()
+            .filter_map(|(k, v)| Some((k.to_string(), v.as_ref().copied()?.to_string())))
+            .collect();
+        let graph = value.graph.iter().map(|x| x.to_string()).collect();
+        let done = value.done.iter().map(|x| x.to_string()).collect();
+        let in_flight = value
+            .in_flight
+            .iter()
+            .filter_map(|(pkg, x)| {
+                let x = x.as_ref()?;
+                Some(InFlightJson {
+                    pkg: pkg.to_string(),
+                    stage: x.stage.into(),
+                    backup: x.backup,
+                })
+            })
+            .collect();
+        Self {
+            pkgs,
+            requested,
+            providers,
+            graph,
+            done,
+            in_flight,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SyncJournalJson {
+    /// Packages requested to sync
+    pub pkgs: Vec<String>,
+    /// Packages requested explicitly by the user
+    #[serde(default)]
+    pub requested: Vec<String>,
+    /// Binaries mapping to the selected provider
+    #[serde(default)]
+    pub providers: BTreeMap<String, String>,
+    /// The planned sync order
+    pub graph: Vec<String>,
+    /// Packages that are done syncing
+    #[serde(default)]
+    pub done: Vec<String>,
+    /// Packages being synced
+    #[serde(default)]
+    pub in_flight: Vec<InFlightJson>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct InFlightJson {
+    /// The package being synced
+    pub pkg: String,
+    /// The stage of the package being synced
+    pub stage: StageJson,
+    /// If a backup of the package is pending restore
+    #[serde(default)]
+    pub backup: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum StageJson {
+    Verify,
+    Backup,
+    Download,
+    Install,
+    Configure,
+    Clean,
+    Uninstall,
+}
+
+impl From<Stage> for StageJson {
+    fn from(value: Stage) -> Self {
+        match value {
+            Stage::Verify => Self::Verify,
+            Stage::Backup => Self::Backup,
+            Stage::Download => Self::Download,
+            Stage::Install => Self::Install,
+            Stage::Configure => Self::Configure,
+            Stage::Clean => Self::Clean,
+            Stage::Uninstall => Self::Uninstall,
+        }
+    }
+}
+
+impl From<StageJson> for Stage {
+    fn from(value: StageJson) -> Self {
+        match value {
+            StageJson::Verify => Self::Verify,
+            StageJson::Backup => Self::Backup,
+            StageJson::Download => Self::Download,
+            StageJson::Install => Self::Install,
+            StageJson::Configure => Self::Configure,
+            StageJson::Clean => Self::Clean,
+            StageJson::Uninstall => Self::Uninstall,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_journal_json_round_trip() {
+        let content = r#"{
+  "pkgs": ["python", "cargo-binstall"],
+  "requested": ["python"],
+  "providers": {"cargo-binstall": "cargo-binstall"},
+  "graph": ["cargo-binstall", "python"],
+  "done": ["cargo-binstall"],
+  "in_flight": [{"pkg": "python", "stage": "install", "backup": true}]
+}"#;
+        let json: SyncJournalJson = json::parse(content).unwrap();
+        let journal = SyncJournal::from(json);
+        assert_eq!(journal.pkgs, PkgId::Python | PkgId::CargoBinstall);
+        assert_eq!(journal.requested, EnumSet::only(PkgId::Python));
+        assert_eq!(
+            journal.providers[BinId::CargoBinstall],
+            Some(PkgId::CargoBinstall)
+        );
+        assert_eq!(journal.graph, vec![PkgId::CargoBinstall, PkgId::Python]);
+        assert_eq!(journal.done, EnumSet::only(PkgId::CargoBinstall));
+        let in_flight = journal.in_flight[PkgId::Python].unwrap();
+        assert_eq!(in_flight.stage, Stage::Install);
+        assert!(in_flight.backup);
+        assert!(journal.in_flight[PkgId::CargoBinstall].is_none());
+
+        let output = json::stringify(&SyncJournalJson::from(&journal)).unwrap();
+        let json: SyncJournalJson = json::parse(&output).unwrap();
+        let journal2 = SyncJournal::from(json);
+        assert_eq!(journal2.pkgs, journal.pkgs);
+        assert_eq!(journal2.requested, journal.requested);
+        assert_eq!(journal2.providers, journal.providers);
+        assert_eq!(journal2.graph, journal.graph);
+        assert_eq!(journal2.done, journal.done);
+        let in_flight = journal2.in_flight[PkgId::Python].unwrap();
+        assert_eq!(in_flight.stage, Stage::Install);
+        assert!(in_flight.backup);
+    }
+
+    #[test]
+    fn test_journal_rewrite() {
+        hmgr::paths::init_test_home();
+        let graph = vec![PkgId::CargoBinstall, PkgId::Python, PkgId::Starship];
+        let pkgs = graph.iter().copied().collect();
+        let mut journal = SyncJournal::new(pkgs, pkgs, EnumMap::default(), graph.clone());
+        journal.save().unwrap();
+        let load = || SyncJournal::load().unwrap().unwrap();
+
+        // python and starship are synced at the same time
+        journal.finish(PkgId::CargoBinstall).unwrap();
+        journal.set_stage(PkgId::Python, Stage::Verify).unwrap();
+        journal.set_stage(PkgId::Starship, Stage::Backup).unwrap();
+        journal.set_backup(PkgId::Starship, true).unwrap();
+        journal.set_stage(PkgId::Starship, Stage::Install).unwrap();
+        let loaded = load();
+        assert_eq!(loaded.graph, graph);
+        assert_eq!(loaded.done, EnumSet::only(PkgId::CargoBinstall));
+        let python = loaded.in_flight[PkgId::Python].unwrap();
+        assert_eq!(python.stage, Stage::Verify);
+        assert!(!python.backup);
+        // the backup is kept when the stage changes
+        let starship = loaded.in_flight[PkgId::Starship].unwrap();
+        assert_eq!(starship.stage, Stage::Install);
+        assert!(starship.backup);
+
+        // a dry run does not change the journal
+        assert!(check_interrupted_sync(true).unwrap());
+        assert!(load().in_flight[PkgId::Starship].is_some_and(|x| x.backup));
+
+        journal.fail(PkgId::Starship).unwrap();
+        journal.finish(PkgId::Python).unwrap();
+        let loaded = load();
+        assert_eq!(loaded.done, PkgId::CargoBinstall | PkgId::Python);
+        assert!(loaded.in_flight.values().all(|x| x.is_none()));
+
+        // nothing was in flight, so there is nothing to restore
+        assert!(check_interrupted_sync(false).unwrap());
+        SyncJournal::remove().unwrap();
+        assert!(SyncJournal::load().unwrap().is_none());
+        assert!(!check_interrupted_sync(false).unwrap());
+    }
+
+    #[test]
+    fn test_journal_json_stage() {
+        let json = InFlightJson {
+            pkg: "python".to_string(),
+            stage: Stage::Configure.into(),
+            backup: false,
+        };
+        let output = json::stringify(&json).unwrap();
+        assert!(output.contains(r#""stage":"configure""#), "{output}");
+        let invalid = r#"{"pkg": "python", "stage": 4}"#;
+        assert!(json::parse::<InFlightJson>(invalid).is_err());
+    }
+}
@@ -0,0 +1,192 @@
+//! Append-only log of the operations that changed the machine, in HOME/history.jsonl
+
+use std::io::Write as _;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use corelib::hmgr;
+use cu::pre::*;
+use registry::{PkgId, Verified};
+
+/// Packages touched by the current operation
+static PACKAGES: Mutex<Vec<HistoryPackage>> = Mutex::new(vec![]);
+
+/// One operation in the history
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Seconds since UNIX epoch
+    pub timestamp: u64,
+    /// Commit of the local shaft repo, if cloned
+    #[serde(default)]
+    pub commit: Option<String>,
+    /// Name of the operation, such as `sync`
+    pub operation: String,
+    /// The command line arguments
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Packages touched by the operation
+    #[serde(default)]
+    pub packages: Vec<HistoryPackage>,
+    /// The error if the operation failed
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// A package touched by an operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryPackage {
+    pub name: String,
+    /// What was done to the package, such as the sync type
+    pub action: String,
+    /// Verify state before the operation
+    #[serde(default)]
+    pub before: Option<String>,
+    /// Verify state after the operation, `None` if not verified
+    /// (for example, if the operation failed)
+    #[serde(default)]
+    pub after: Option<String>,
+}
+
+/// Record that a package is touched by the current operation
+pub fn record_package(pkg: PkgId, action: impl std::fmt::Display, before: Option<&Verified>) {
+    let package = HistoryPackage {
+        name: pkg.to_string(),
+        action: action.to_string(),
+        before: before.map(|x| verified_str(x).to_string()),
+        after: None,
+    };
+    if let Ok(mut packages) = PACKAGES.lock() {
+        packages.push(package);
+    }
+}
+
+/// Record the verify state after the package is changed by the current operation
+pub fn record_after(pkg: PkgId, after: &Verified) {
+    let Ok(mut packages) = PACKAGES.lock() else {
+        return;
+    };
+    if let Some(package) = packages.iter_mut().rev().find(|x| x.name == pkg.to_str()) {
+        package.after = Some(verified_str(after).to_string());
+    }
+}
+
+/// Append the operation that just finished to the history.
+///
+/// Failure to write the history does not fail the operation
+pub fn append(operation: &str, result: &cu::Result<()>) {
+    let packages = PACKAGES
+        .lock()
+        .map(|mut x| std::mem::take(&mut *x))
+        .unwrap_or_default();
+    let commit = match hmgr::repo::get_commit() {
+        Ok(x) => x,
+        Err(e) => {
+            cu::debug!("failed to get commit for history: {e:?}");
+            None
+        }
+    };
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|x| x.as_secs())
+        .unwrap_or_default();
+    let entry = HistoryEntry {
+        timestamp,
+        commit,
+        operation: operation.to_string(),
+        args: std::env::args().skip(1).collect(),
+        packages,
+        error: result.as_ref().err().map(|e| format!("{e:?}")),
+    };
+    if let Err(e) = append_entry(&entry) {
+        cu::warn!("failed to record operation to history: {e:?}");
+    }
+}
+
+#[cu::context("failed to append to history")]
+fn append_entry(entry: &HistoryEntry) -> cu::Result<()> {
+    let mut line = json::stringify(entry)?;
+    line.push('\n');
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(hmgr::paths::history_jsonl())?;
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// Load all entries in the history, oldest first
+#[cu::context("failed to load history")]
+pub fn load() -> cu::Result<Vec<HistoryEntry>> {
+    let path = hmgr::paths::history_jsonl();
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let content = cu::fs::read_string(path)?;
+    let mut entries: Vec<HistoryEntry> = vec![];
+    for (i, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match json::parse(line) {
+            Ok(x) => entries.push(x),
+            Err(e) => cu::warn!("skipping invalid history entry on line {}: {e}", i + 1),
+        }
+    }
+    Ok(entries)
+}
+
+fn verified_str(verified: &Verified) -> &'static str {
+    match verified {
+        Verified::UpToDate(_) => "up-to-date",
+        Verified::NotUpToDate(_) => "outdated",
+        Verified::NotInstalled => "not-installed",
+        Verified::NeedsConfig => "needs-config",
+    }
+}
+
+/// Format the timestamp as `YYYY-MM-DD HH:MM:SS` in UTC
+pub fn format_timestamp(timestamp: u64) -> String {
+    let days = (timestamp / 86400) as i64;
+    let secs = timestamp % 86400;
+    // civil from days, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}",
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_format_timestamp() {
+        let cases = [
+            (0, "1970-01-01 00:00:00"),
+            (86399, "1970-01-01 23:59:59"),
+            // leap year divisible by 400
+            (951825600, "2000-02-29 12:00:00"),
+            (951868800, "2000-03-01 00:00:00"),
+            (1709251199, "2024-02-29 23:59:59"),
+            // not a leap year, divisible by 100
+            (4107542399, "2100-02-28 23:59:59"),
+            (4107542400, "2100-03-01 00:00:00"),
+            (1735689599, "2024-12-31 23:59:59"),
+            (1735689600, "2025-01-01 00:00:00"),
+        ];
+        for (timestamp, expected) in cases {
+            assert_eq!(format_timestamp(timestamp), expected, "{timestamp}");
+        }
+    }
+}
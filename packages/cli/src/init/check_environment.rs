@@ -24,7 +24,10 @@ pub mod windows {
                 cu::warn!("user profile is '{}'", user_profile);
                 cu::warn!("the 'HOME' user environment variable is not set to %USERPROFILE%");
                 cu::hint!("this may cause compatibility issue");
-                if cu::yesno!("change HOME to %USERPROFILE% ?")? {
+                if crate::interactive::yesno(
+                    "change HOME to %USERPROFILE% ?",
+                    "rerun with --yes to change HOME, or set `windows.control-home = false` in core.toml to skip this check",
+                )? {
                     cu::check!(
                         hmgr::windows::set_user("HOME", &user_profile),
                         "failed to set user 'HOME'"
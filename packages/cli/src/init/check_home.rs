@@ -5,6 +5,8 @@ use cu::pre::*;
 
 use corelib::hmgr;
 
+use crate::interactive;
+
 pub fn check_init_home() -> cu::Result<()> {
     let home_path_str = cu::env_var("SHAFT_HOME")?;
     let home_path = Path::new(&home_path_str).normalize()?;
@@ -15,8 +17,9 @@ pub fn check_init_home() -> cu::Result<()> {
 
     let home = if !home_path_str.is_empty() {
         cu::warn!("did not find home at: '{}'", home_path.display());
-        if !cu::yesno!(
-            "do you want to try creating an empty directory here as the home for this tool?"
+        if !interactive::yesno(
+            "do you want to try creating an empty directory here as the home for this tool?",
+            "create the directory, or correct SHAFT_HOME, then rerun; or rerun with --yes to create it",
         )? {
             cu::bail!("SHAFT_HOME does not point to an existing directory");
         }
@@ -30,7 +33,10 @@ if you already initialized the tool, make sure you have added the required initi
         if cfg!(not(windows)) {
             cu::warn!("note: only bash and zsh are supported");
         }
-        if !cu::yesno!("do you want to initialize the tool now")? {
+        if !interactive::yesno(
+            "do you want to initialize the tool now?",
+            "rerun with --yes to initialize with the default home, or set SHAFT_HOME to choose the location",
+        )? {
             cu::bail!("SHAFT_HOME not set, please follow the prompts to initialize the tool");
         }
         let default_home = if cfg!(windows) {
@@ -44,7 +50,12 @@ the workaround is put the .vhdx on the OS drive. You can use Event Viewer to ins
 to see why the auto-mount fails.
         "
             );
-            let dev_drive = cu::prompt!("if you want to set up SHAFT_HOME on a Windows Dev Drive, enter the drive letter; otherwise press ENTER")?.to_ascii_uppercase();
+            let dev_drive = if interactive::is_unattended() {
+                // set SHAFT_HOME to use a dev drive when unattended
+                String::new()
+            } else {
+                cu::prompt!("if you want to set up SHAFT_HOME on a Windows Dev Drive, enter the drive letter; otherwise press ENTER")?.to_ascii_uppercase()
+            };
             let default_home = if dev_drive.is_empty() {
                 match std::env::home_dir() {
                     Some(mut x) => {
@@ -66,7 +77,11 @@ to see why the auto-mount fails.
                 None => PathBuf::from("/opt/pistonite-shaft"),
             }
         };
-        prompt_user_input_for_home(&default_home)?
+        if interactive::is_unattended() {
+            check_home_path(&default_home)?
+        } else {
+            prompt_user_input_for_home(&default_home)?
+        }
     };
     let bar = cu::progress("initializing home").spawn();
     cu::progress!(bar, "creating home directory");
@@ -123,7 +138,9 @@ to see why the auto-mount fails.
         cu::hint!("you can replace `.bash` with the shell you use");
     }
 
-    cu::prompt!("please press ENTER to continue once it's added")?;
+    if !interactive::is_unattended() {
+        cu::prompt!("please press ENTER to continue once it's added")?;
+    }
 
     hmgr::add_env_assert([("SHAFT_HOME".to_string(), home_str.to_string())])?;
     hmgr::require_envchange_reinvocation()
@@ -145,21 +162,33 @@ press ENTER to accept the default, or enter another path",
             } else {
                 std::mem::take(answer).into()
             };
-            let user_selected_home = user_selected_home.normalize()?;
-            if user_selected_home.as_utf8().is_err() {
-                cu::error!("selected home path is not utf-8, please choose another location");
-                return Ok(false);
-            }
-            if let Ok(false) = cu::fs::is_empty_dir(&user_selected_home) {
-                cu::error!(
-                    "selected home path is a non-empty directory, please choose another location"
-                );
-                return Ok(false);
+            match check_home_path(&user_selected_home) {
+                Ok(x) => {
+                    output = x;
+                    Ok(true)
+                }
+                Err(e) => {
+                    cu::error!("{e}, please choose another location");
+                    Ok(false)
+                }
             }
-            output = user_selected_home;
-            Ok(true)
         })
         .run()?;
 
     Ok(output)
 }
+
+/// Check the path can be used as the home, returns the normalized path
+fn check_home_path(home: &Path) -> cu::Result<PathBuf> {
+    let home = home.normalize()?;
+    if home.as_utf8().is_err() {
+        cu::bail!("selected home path is not utf-8");
+    }
+    if let Ok(false) = cu::fs::is_empty_dir(&home) {
+        cu::bail!(
+            "selected home path is a non-empty directory: '{}'",
+            home.display()
+        );
+    }
+    Ok(home)
+}
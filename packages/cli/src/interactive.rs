@@ -0,0 +1,25 @@
+//! Handling of prompts when running unattended, see [`corelib::interactive`]
+
+pub use corelib::interactive::{ensure_input, init, is_unattended, is_yes};
+
+/// Env var to turn on `--yes`
+pub const ENV_YES: &str = "SHAFT_YES";
+/// Env var to turn on `--no-input`
+pub const ENV_NO_INPUT: &str = "SHAFT_NO_INPUT";
+
+/// Check if the env var is set to a truthy value
+pub fn env_flag(key: &str) -> bool {
+    let value = cu::env_var(key).unwrap_or_default();
+    let value = value.trim();
+    !value.is_empty() && value != "0" && !value.eq_ignore_ascii_case("false")
+}
+
+/// Ask a yes/no question.
+///
+/// Answered with yes by cu when `--yes`, and fails with the `fix` hint when `--no-input`
+pub fn yesno(question: &str, fix: &str) -> cu::Result<bool> {
+    if !is_yes() {
+        ensure_input(question, fix)?;
+    }
+    cu::yesno!("{question}")
+}
@@ -3,4 +3,7 @@ pub use api::CliApi;
 mod cmds;
 mod config;
 mod graph;
+mod history;
 mod init;
+mod interactive;
+mod manifest;
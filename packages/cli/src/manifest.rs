@@ -0,0 +1,133 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use cu::pre::*;
+use enum_map::EnumMap;
+use enumset::EnumSet;
+use registry::{BinId, PkgId};
+
+/// Declarative manifest of the desired state of the machine
+///
+/// ```toml
+/// # packages to install, dependencies are resolved automatically
+/// packages = ["git", "nvim"]
+///
+/// # provider for binaries with multiple providers
+/// [providers]
+/// clang = "system-cctools"
+///
+/// # config overrides for each package
+/// [config.nvim]
+/// nvim-tree-git = true
+/// ```
+#[derive(Debug, Default)]
+pub struct Manifest {
+    /// Packages to install
+    pub pkgs: EnumSet<PkgId>,
+    /// Provider selection for binaries
+    pub providers: EnumMap<BinId, Option<PkgId>>,
+    /// Config values to merge into the config of each package
+    pub configs: Vec<(PkgId, toml::Table)>,
+}
+
+impl Manifest {
+    #[cu::context("failed to load manifest '{}'", path.display())]
+    pub fn load(path: &Path) -> cu::Result<Self> {
+        let content = cu::fs::read_string(path)?;
+        let manifest: ManifestToml = toml::parse(&content)?;
+
+        let mut pkgs = EnumSet::new();
+        for name in &manifest.packages {
+            let pkg = cu::check!(PkgId::from_str(name), "cannot find package '{name}'")?;
+            if !pkg.package().enabled() {
+                cu::bail!("package '{pkg}' is not supported on the current platform");
+            }
+            pkgs.insert(pkg);
+        }
+
+        let mut providers: EnumMap<BinId, Option<PkgId>> = EnumMap::default();
+        for (bin, pkg) in &manifest.providers {
+            let bin_id = cu::check!(BinId::from_str(bin), "cannot find binary '{bin}'")?;
+            let pkg_id = cu::check!(PkgId::from_str(pkg), "cannot find package '{pkg}'")?;
+            if !bin_id.providers().contains(pkg_id) {
+                cu::bail!("package '{pkg_id}' does not provide binary '{bin_id}'");
+            }
+            providers[bin_id] = Some(pkg_id);
+        }
+
+        let mut configs = Vec::with_capacity(manifest.config.len());
+        for (name, values) in manifest.config {
+            let pkg = cu::check!(PkgId::from_str(&name), "cannot find package '{name}'")?;
+            if pkg.package().config_def().is_none() {
+                cu::bail!("package '{pkg}' does not have a config file that can be overridden");
+            }
+            configs.push((pkg, values));
+        }
+
+        Ok(Self {
+            pkgs,
+            providers,
+            configs,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestToml {
+    /// Packages to install
+    #[serde(default)]
+    pub packages: Vec<String>,
+    /// Binary mapping to the package to provide it
+    #[serde(default)]
+    pub providers: BTreeMap<String, String>,
+    /// Package mapping to the config values to override
+    #[serde(default)]
+    pub config: BTreeMap<String, toml::Table>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn load_str(name: &str, content: &str) -> cu::Result<Manifest> {
+        let dir = std::env::temp_dir().join(format!("shaft-cli-test-manifest-{name}"));
+        cu::fs::make_dir_empty(&dir)?;
+        let path = dir.join("shaft.toml");
+        cu::fs::write(&path, content)?;
+        Manifest::load(&path)
+    }
+
+    #[test]
+    fn load_manifest() -> cu::Result<()> {
+        let manifest = load_str(
+            "ok",
+            r#"
+packages = ["python", "starship"]
+[providers]
+python = "system-python"
+[config.starship]
+build_from_source = true
+"#,
+        )?;
+        assert_eq!(manifest.pkgs, PkgId::Python | PkgId::Starship);
+        assert_eq!(manifest.providers[BinId::Python], Some(PkgId::SystemPython));
+        assert_eq!(manifest.providers[BinId::Git], None);
+        assert_eq!(manifest.configs.len(), 1);
+        let (pkg, values) = &manifest.configs[0];
+        assert_eq!(*pkg, PkgId::Starship);
+        assert_eq!(
+            values.get("build_from_source"),
+            Some(&toml::Value::Boolean(true))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn load_manifest_errors() {
+        assert!(load_str("unknown-pkg", r#"packages = ["not-a-package"]"#).is_err());
+        assert!(load_str("unknown-field", r#"package = ["python"]"#).is_err());
+        assert!(load_str("bad-provider", "[providers]\npython = \"cargo-binstall\"").is_err());
+        assert!(load_str("no-config", "[config.cargo-binstall]\nfoo = 1").is_err());
+    }
+}
@@ -5,7 +5,8 @@ use cu::pre::*;
 
 use crate::internal;
 
-internal::main_thread_singleton! {
+internal::locked_singleton! {
+    #[session]
     const brew = Brew::new();
 }
 
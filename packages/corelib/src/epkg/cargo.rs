@@ -6,7 +6,8 @@ use cu::pre::*;
 
 use crate::{hmgr, internal};
 
-internal::main_thread_singleton! {
+internal::locked_singleton! {
+    #[session]
     const cargo = Cargo::new();
 }
 
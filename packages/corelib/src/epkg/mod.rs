@@ -5,3 +5,6 @@ pub mod cargo;
 pub mod pacman;
 #[cfg(windows)]
 pub mod winget;
+
+mod session;
+pub use session::{PackageSession, begin_package_session};
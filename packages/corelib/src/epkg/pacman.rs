@@ -8,7 +8,8 @@ use cu::pre::*;
 
 use crate::{internal, opfs};
 
-internal::main_thread_singleton! {
+internal::locked_singleton! {
+    #[session]
     const pacman = Pacman::new();
 }
 
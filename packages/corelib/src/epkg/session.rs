@@ -0,0 +1,106 @@
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::sync::{Mutex, MutexGuard};
+
+/// Held by the package session that is using the external package managers
+static SESSION_LOCK: Mutex<()> = Mutex::new(());
+
+enum SessionState {
+    /// Not in a session
+    None,
+    /// In a session that has not used the external package managers yet
+    Started,
+    /// In a session that is using the external package managers
+    Locked(#[allow(unused)] MutexGuard<'static, ()>),
+}
+
+thread_local! {
+    static SESSION: RefCell<SessionState> = const { RefCell::new(SessionState::None) };
+}
+
+/// Keep the external package managers to the current thread once they are used,
+/// until the returned guard is dropped.
+///
+/// When packages are synced at the same time, this makes sure the operations
+/// of one package (for example, multiple pacman or cargo commands) are not
+/// interleaved with the ones of another package. Nested sessions on the same
+/// thread are part of the outer session
+pub fn begin_package_session() -> PackageSession {
+    let outer = SESSION.with_borrow_mut(|state| {
+        if !matches!(state, SessionState::None) {
+            return false;
+        }
+        *state = SessionState::Started;
+        true
+    });
+    PackageSession {
+        outer,
+        _not_send: PhantomData,
+    }
+}
+
+/// Guard for [`begin_package_session`]
+pub struct PackageSession {
+    outer: bool,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for PackageSession {
+    fn drop(&mut self) {
+        if self.outer {
+            let state = SESSION.replace(SessionState::None);
+            drop(state);
+        }
+    }
+}
+
+/// Called when an external package manager is used. If the current thread is in a
+/// session, take the session lock, blocking until other sessions using the
+/// package managers end
+pub(crate) fn join_session() {
+    let started = SESSION.with_borrow(|state| matches!(state, SessionState::Started));
+    if !started {
+        return;
+    }
+    let lock = SESSION_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    SESSION.set(SessionState::Locked(lock));
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_package_session() {
+        // joining again in the same session, or in a nested session, does not block
+        {
+            let _session = begin_package_session();
+            join_session();
+            let nested = begin_package_session();
+            join_session();
+            drop(nested);
+            join_session();
+        }
+        // sessions on other threads are serialized once they join
+        let active = AtomicUsize::new(0);
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    let _session = begin_package_session();
+                    join_session();
+                    assert_eq!(active.fetch_add(1, Ordering::SeqCst), 0);
+                    std::thread::sleep(Duration::from_millis(10));
+                    active.fetch_sub(1, Ordering::SeqCst);
+                    // still held after the package manager is done
+                    join_session();
+                });
+            }
+        });
+        // not blocked when outside of a session
+        join_session();
+        let _lock = SESSION_LOCK.try_lock().unwrap();
+    }
+}
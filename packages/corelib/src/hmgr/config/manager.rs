@@ -14,6 +14,9 @@ pub struct ConfigDef<T> {
     /// empty script indicates compatible change - such as
     /// new config values being added
     pub migration_scripts: &'static [&'static str],
+    /// Parse the content as the typed config object, kept when
+    /// converting to untyped, so the content can still be validated
+    validate_fn: fn(&str) -> cu::Result<()>,
 
     _marker: PhantomData<T>,
 }
@@ -24,7 +27,13 @@ impl<T> Clone for ConfigDef<T> {
 }
 impl<T> Copy for ConfigDef<T> {}
 
-impl<T> ConfigDef<T> {
+/// Config definition where the config is loaded as an untyped TOML table
+pub type UntypedConfigDef = ConfigDef<toml::Table>;
+
+impl<T> ConfigDef<T>
+where
+    for<'de> T: Deserialize<'de>,
+{
     pub const fn new(
         template_str: &'static str,
         migration_scripts: &'static [&'static str],
@@ -32,15 +41,30 @@ impl<T> ConfigDef<T> {
         Self {
             template_str,
             migration_scripts,
+            validate_fn: validate_typed::<T>,
             _marker: PhantomData,
         }
     }
-}
-
-impl<T> ConfigDef<T>
-where
-    for<'de> T: Deserialize<'de>,
-{
+    /// Get the definition that loads the config as an untyped TOML table
+    pub const fn untyped(self) -> UntypedConfigDef {
+        ConfigDef {
+            template_str: self.template_str,
+            migration_scripts: self.migration_scripts,
+            validate_fn: self.validate_fn,
+            _marker: PhantomData,
+        }
+    }
+    /// Check if the content of the configuration file can be parsed as the typed
+    /// config object. Content of older versions is only checked to be valid TOML,
+    /// since it will be migrated when loaded
+    pub fn validate(self, content: &str) -> cu::Result<()> {
+        let version = config::peek_version(content).unwrap_or(0);
+        if version < self.current_version() {
+            toml::parse::<toml::Table>(content)?;
+            return Ok(());
+        }
+        (self.validate_fn)(content)
+    }
     pub fn load_default(self) -> cu::Result<T> {
         let template = toml::parse::<ConfigTemplate>(self.template_str)?;
         let content = config::serialize_config_template(&template, self.current_version());
@@ -122,7 +146,280 @@ where
             "failed to parse typed config object"
         )
     }
+    /// Merge the values into the configuration file, perform migration if needed.
+    ///
+    /// The file is re-serialized with the template, so the comments are kept.
+    /// Returns if the file content is changed
+    #[inline(always)]
+    pub fn merge(self, path: impl AsRef<Path>, values: &toml::Table) -> cu::Result<bool> {
+        self.merge_impl(path.as_ref(), values)
+    }
+    fn merge_impl(self, path: &Path, values: &toml::Table) -> cu::Result<bool> {
+        // make sure the file exists and is migrated
+        self.load_impl(path)?;
+        let file_content = cu::fs::read_string(path)?;
+        let mut object = cu::check!(
+            toml::parse::<toml::Table>(&file_content),
+            "failed to parse config file as TOML: '{}'",
+            path.display()
+        )?;
+        merge_table(&mut object, values);
+        let template = toml::parse::<ConfigTemplate>(self.template_str)?;
+        let content = config::serialize_config(&template, self.current_version(), &mut object);
+        let (unused_count, unused_repr) = config::serialize_leaf_key_values(&object);
+        if unused_count > 0 {
+            cu::warn!("there were {unused_count} unused config keys:\n{unused_repr}");
+        }
+        if content == file_content {
+            return Ok(false);
+        }
+        cu::check!(
+            self.validate(&content),
+            "failed to parse typed config object after merging"
+        )?;
+        cu::fs::write(path, &content)?;
+        Ok(true)
+    }
+    /// Get the value of the dotted key in the configuration file, perform migration if needed.
+    ///
+    /// The key must exist in the template. The default value is returned if the key is not set
+    pub fn get_value(self, path: impl AsRef<Path>, key: &str) -> cu::Result<toml::Value> {
+        let path = path.as_ref();
+        let template = toml::parse::<ConfigTemplate>(self.template_str)?;
+        let key_path = key.split('.').collect::<Vec<_>>();
+        let default = cu::check!(
+            template.default_value(&key_path),
+            "'{key}' is not a config key"
+        )?;
+        self.load_impl(path)?;
+        let file_content = cu::fs::read_string(path)?;
+        let object = cu::check!(
+            toml::parse::<toml::Table>(&file_content),
+            "failed to parse config file as TOML: '{}'",
+            path.display()
+        )?;
+        let value = get_by_key(&object, &key_path).unwrap_or(default);
+        Ok(value.clone())
+    }
+    /// Set the value of the dotted key in the configuration file, perform migration if needed.
+    ///
+    /// The key must exist in the template, and the value must have the same type as the default.
+    /// Returns if the file content is changed
+    pub fn set_value(self, path: impl AsRef<Path>, key: &str, value: &str) -> cu::Result<bool> {
+        let template = toml::parse::<ConfigTemplate>(self.template_str)?;
+        let key_path = key.split('.').collect::<Vec<_>>();
+        let default = cu::check!(
+            template.default_value(&key_path),
+            "'{key}' is not a config key"
+        )?;
+        let value = cu::check!(
+            config::parse_value_like(value, default),
+            "invalid value for '{key}'"
+        )?;
+        self.merge_impl(path.as_ref(), &nest_value(&key_path, value))
+    }
+    /// Reset the configuration file to the template defaults, or only the dotted key
+    /// if specified. Returns if the file content is changed
+    pub fn reset(self, path: impl AsRef<Path>, key: Option<&str>) -> cu::Result<bool> {
+        let path = path.as_ref();
+        let template = toml::parse::<ConfigTemplate>(self.template_str)?;
+        let Some(key) = key else {
+            let content = config::serialize_config_template(&template, self.current_version());
+            let file_content = cu::fs::read_string(path).ok();
+            if file_content.as_deref() == Some(content.as_str()) {
+                return Ok(false);
+            }
+            cu::fs::write(path, &content)?;
+            return Ok(true);
+        };
+        let key_path = key.split('.').collect::<Vec<_>>();
+        let default = cu::check!(
+            template.default_value(&key_path),
+            "'{key}' is not a config key"
+        )?;
+        self.load_impl(path)?;
+        let file_content = cu::fs::read_string(path)?;
+        let mut object = cu::check!(
+            toml::parse::<toml::Table>(&file_content),
+            "failed to parse config file as TOML: '{}'",
+            path.display()
+        )?;
+        // replace instead of merge, so keys not in the default table are also reset
+        set_by_key(&mut object, &key_path, default.clone());
+        let content = config::serialize_config(&template, self.current_version(), &mut object);
+        if content == file_content {
+            return Ok(false);
+        }
+        cu::check!(
+            self.validate(&content),
+            "failed to parse typed config object after resetting"
+        )?;
+        cu::fs::write(path, &content)?;
+        Ok(true)
+    }
+    /// Get the values in the configuration file that are different from the template defaults,
+    /// perform migration if needed.
+    ///
+    /// Returns the different values, and the unknown keys (count and serialized)
+    pub fn diff(self, path: impl AsRef<Path>) -> cu::Result<(Vec<ConfigDiff>, (usize, String))> {
+        let path = path.as_ref();
+        self.load_impl(path)?;
+        let file_content = cu::fs::read_string(path)?;
+        let mut object = cu::check!(
+            toml::parse::<toml::Table>(&file_content),
+            "failed to parse config file as TOML: '{}'",
+            path.display()
+        )?;
+        let template = toml::parse::<ConfigTemplate>(self.template_str)?;
+        let mut diffs = vec![];
+        for (key_path, default) in template.entries() {
+            let Some(value) = get_by_key(&object, &key_path) else {
+                continue;
+            };
+            if value != default {
+                diffs.push(ConfigDiff {
+                    key: key_path.join("."),
+                    value: value.clone(),
+                    default: default.clone(),
+                });
+            }
+        }
+        // serializing removes the known keys, the remaining are unknown
+        config::serialize_config(&template, self.current_version(), &mut object);
+        Ok((diffs, config::serialize_leaf_key_values(&object)))
+    }
     pub const fn current_version(self) -> usize {
         self.migration_scripts.len()
     }
 }
+
+/// A value in the configuration file that is different from the template default
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigDiff {
+    /// Dotted path of the key
+    pub key: String,
+    pub value: toml::Value,
+    pub default: toml::Value,
+}
+
+/// Get the value at the key path in the table
+fn get_by_key<'a>(table: &'a toml::Table, key_path: &[&str]) -> Option<&'a toml::Value> {
+    let (last, parents) = key_path.split_last()?;
+    let mut table = table;
+    for k in parents {
+        table = table.get(*k)?.as_table()?;
+    }
+    table.get(*last)
+}
+
+/// Set the value at the key path in the table, creating the parent tables if needed
+fn set_by_key(table: &mut toml::Table, key_path: &[&str], value: toml::Value) {
+    let Some((last, parents)) = key_path.split_last() else {
+        return;
+    };
+    let mut table = table;
+    for k in parents {
+        let next = table
+            .entry(k.to_string())
+            .or_insert_with(|| toml::Value::Table(Default::default()));
+        if !next.is_table() {
+            *next = toml::Value::Table(Default::default());
+        }
+        let toml::Value::Table(next) = next else {
+            return;
+        };
+        table = next;
+    }
+    table.insert(last.to_string(), value);
+}
+
+/// Wrap the value in tables so it's at the key path
+fn nest_value(key_path: &[&str], value: toml::Value) -> toml::Table {
+    let mut value = value;
+    for k in key_path.iter().rev() {
+        let mut table = toml::Table::new();
+        table.insert(k.to_string(), value);
+        value = toml::Value::Table(table);
+    }
+    match value {
+        toml::Value::Table(table) => table,
+        _ => toml::Table::new(),
+    }
+}
+
+fn validate_typed<T>(content: &str) -> cu::Result<()>
+where
+    for<'de> T: Deserialize<'de>,
+{
+    toml::parse::<T>(content)?;
+    Ok(())
+}
+
+/// Recursively merge the values into the target table
+fn merge_table(target: &mut toml::Table, values: &toml::Table) {
+    for (key, value) in values {
+        if let (Some(toml::Value::Table(target)), toml::Value::Table(value)) =
+            (target.get_mut(key), value)
+        {
+            merge_table(target, value);
+            continue;
+        }
+        target.insert(key.clone(), value.clone());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Debug, Deserialize)]
+    struct TestConfig {
+        bar: String,
+        aaa: TestConfigAaa,
+    }
+    #[derive(Debug, Deserialize)]
+    struct TestConfigAaa {
+        foo: f64,
+        enabled: bool,
+    }
+
+    static TEMPLATE: &str = r##"
+[[section]]
+key = []
+children = [
+    { key = ["bar"], value = "hello" },
+]
+[[section]]
+key = ["aaa"]
+children = [
+    { key = ["foo"], value = 0.5 },
+    { key = ["enabled"], value = false },
+]
+    "##;
+
+    #[test]
+    fn merge_values() -> cu::Result<()> {
+        let def = ConfigDef::<TestConfig>::new(TEMPLATE, &[]);
+        let dir = std::env::temp_dir().join("shaft-corelib-test-config-merge");
+        cu::fs::make_dir_empty(&dir)?;
+        let path = dir.join("config.toml");
+
+        // the file is created from the template before merging
+        let values = toml::parse::<toml::Table>("aaa = { foo = 2.0 }")?;
+        assert!(def.merge(&path, &values)?);
+        let config = def.load(&path)?;
+        assert_eq!(config.bar, "hello");
+        assert_eq!(config.aaa.foo, 2.0);
+        assert!(!config.aaa.enabled);
+
+        // merging the same values again does not change the file
+        assert!(!def.merge(&path, &values)?);
+
+        // values that don't parse as the typed config are rejected
+        let content = cu::fs::read_string(&path)?;
+        let values = toml::parse::<toml::Table>("aaa = { enabled = \"yes\" }")?;
+        assert!(def.merge(&path, &values).is_err());
+        assert_eq!(cu::fs::read_string(&path)?, content);
+        Ok(())
+    }
+}
@@ -32,6 +32,13 @@ pub fn serialize_leaf_key_values(value: &toml::Table) -> (usize, String) {
 
     (count, out)
 }
+/// Serialize a single value the same way as in the configuration file
+pub fn serialize_value(value: &toml::Value) -> String {
+    let mut out = String::new();
+    write_value(value, 0, &mut out);
+    out
+}
+
 /// Serialize a config template into the default configuration file
 pub fn serialize_config_template(template: &ConfigTemplate, version: usize) -> String {
     serialize_config(template, version, &mut Default::default())
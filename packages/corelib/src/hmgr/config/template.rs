@@ -55,6 +55,91 @@ pub struct ConfigEntryContent {
     pub value: toml::Value,
 }
 
+impl ConfigTemplate {
+    /// Get the default value of the key path in the template.
+    /// The key path can also point into a default value that is a table
+    pub fn default_value(&self, key: &[&str]) -> Option<&toml::Value> {
+        for section in &self.sections {
+            let Some(section) = &section.content else {
+                continue;
+            };
+            let Some(key) = strip_key_prefix(key, &section.key) else {
+                continue;
+            };
+            for entry in &section.children {
+                let Some(entry) = &entry.content else {
+                    continue;
+                };
+                let Some(key) = strip_key_prefix(key, &entry.key) else {
+                    continue;
+                };
+                let mut value = &entry.value;
+                for k in key {
+                    value = value.as_table()?.get(*k)?;
+                }
+                return Some(value);
+            }
+        }
+        None
+    }
+}
+
+impl ConfigTemplate {
+    /// Iterate the full key paths and default values of the entries in the template
+    pub fn entries(&self) -> impl Iterator<Item = (Vec<&str>, &toml::Value)> {
+        self.sections
+            .iter()
+            .filter_map(|section| section.content.as_ref())
+            .flat_map(|section| {
+                section
+                    .children
+                    .iter()
+                    .filter_map(|entry| entry.content.as_ref())
+                    .map(|entry| {
+                        let key = section
+                            .key
+                            .iter()
+                            .chain(&entry.key)
+                            .map(|x| x.as_str())
+                            .collect();
+                        (key, &entry.value)
+                    })
+            })
+    }
+}
+
+fn strip_key_prefix<'a, 'b>(key: &'a [&'b str], prefix: &[String]) -> Option<&'a [&'b str]> {
+    if key.len() < prefix.len() {
+        return None;
+    }
+    let (head, tail) = key.split_at(prefix.len());
+    if head.iter().zip(prefix).all(|(a, b)| *a == b) {
+        Some(tail)
+    } else {
+        None
+    }
+}
+
+/// Parse a TOML value from user input, which should have the same type as
+/// the default value. Strings can be specified without quotes
+pub fn parse_value_like(input: &str, default: &toml::Value) -> cu::Result<toml::Value> {
+    let parsed = toml::parse::<toml::Table>(&format!("value = {input}"))
+        .ok()
+        .and_then(|mut x| x.remove("value"));
+    let value = match (parsed, default) {
+        (Some(toml::Value::Integer(x)), toml::Value::Float(_)) => toml::Value::Float(x as f64),
+        (Some(x), _) if x.type_str() == default.type_str() => x,
+        (_, toml::Value::String(_)) => toml::Value::String(input.to_string()),
+        (Some(x), _) => cu::bail!(
+            "expecting a {}, but got a {}: '{input}'",
+            default.type_str(),
+            x.type_str()
+        ),
+        (None, _) => cu::bail!("failed to parse '{input}' as a {}", default.type_str()),
+    };
+    Ok(value)
+}
+
 fn trim_string<'de, D>(deser: D) -> Result<String, D::Error>
 where
     D: serde::Deserializer<'de>,
@@ -76,3 +161,59 @@ where
     }
     Ok(s)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn default_value_and_parse() {
+        let template: ConfigTemplate = toml::parse(
+            r##"
+[[section]]
+key = []
+children = [
+    { key = ["bar"], value = "hello" },
+]
+[[section]]
+key = ["aaa"]
+children = [
+    { key = ["barb", "foo"], value = 0.5 },
+    { key = ["table"], value = { enabled = false } },
+]
+        "##,
+        )
+        .expect("failed to parse");
+        let bar = template.default_value(&["bar"]).unwrap();
+        assert_eq!(bar, &toml::Value::String("hello".to_string()));
+        let foo = template.default_value(&["aaa", "barb", "foo"]).unwrap();
+        assert_eq!(foo, &toml::Value::Float(0.5));
+        let enabled = template
+            .default_value(&["aaa", "table", "enabled"])
+            .unwrap();
+        assert_eq!(enabled, &toml::Value::Boolean(false));
+        assert!(template.default_value(&["aaa", "barb"]).is_none());
+        assert!(template.default_value(&["aaa", "nope"]).is_none());
+        let keys = template
+            .entries()
+            .map(|x| x.0.join("."))
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec!["bar", "aaa.barb.foo", "aaa.table"]);
+
+        assert_eq!(
+            parse_value_like("world", bar).unwrap(),
+            toml::Value::String("world".to_string())
+        );
+        assert_eq!(
+            parse_value_like("123", bar).unwrap(),
+            toml::Value::String("123".to_string())
+        );
+        assert_eq!(parse_value_like("2", foo).unwrap(), toml::Value::Float(2.0));
+        assert_eq!(
+            parse_value_like("true", enabled).unwrap(),
+            toml::Value::Boolean(true)
+        );
+        assert!(parse_value_like("yes", enabled).is_err());
+        assert!(parse_value_like("\"1\"", foo).is_err());
+    }
+}
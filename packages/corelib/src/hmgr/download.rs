@@ -1,5 +1,6 @@
 use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, LazyLock};
 use std::time::Duration;
 
@@ -17,6 +18,81 @@ static CLIENT: LazyLock<Result<Client, String>> = LazyLock::new(|| {
         .map_err(|x| format!("{x}"))
 });
 
+/// A download that [`download_file`] would perform
+#[derive(Debug, Clone)]
+pub struct PlannedDownload {
+    pub identifier: PathBuf,
+    pub url: String,
+    pub sha256_checksum: String,
+    /// Target path in the download cache
+    pub path: PathBuf,
+}
+
+impl PlannedDownload {
+    pub fn new(
+        identifier: impl AsRef<Path>,
+        url: impl AsRef<str>,
+        sha256_checksum: impl AsRef<str>,
+    ) -> Self {
+        let identifier = identifier.as_ref();
+        let url = url.as_ref();
+        Self {
+            path: hmgr::paths::download(identifier, url),
+            identifier: identifier.to_path_buf(),
+            url: url.to_string(),
+            sha256_checksum: sha256_checksum.as_ref().to_ascii_lowercase(),
+        }
+    }
+}
+
+/// Download the planned files concurrently, with at most `max_connections` downloads at a time.
+///
+/// All files are attempted even if some fail to download
+pub fn download_planned(
+    downloads: &[PlannedDownload],
+    max_connections: usize,
+    bar: Option<Arc<cu::ProgressBar>>,
+) -> cu::Result<()> {
+    let total = downloads.len();
+    if total == 0 {
+        return Ok(());
+    }
+    let bar = cu::progress(format!("downloading {total} file(s)"))
+        .parent(bar)
+        .spawn();
+    let next = AtomicUsize::new(0);
+    let finished = AtomicUsize::new(0);
+    let failed = AtomicUsize::new(0);
+    std::thread::scope(|s| {
+        for _ in 0..max_connections.clamp(1, total) {
+            s.spawn(|| {
+                loop {
+                    let i = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(download) = downloads.get(i) else {
+                        break;
+                    };
+                    if let Err(e) = download_file_impl(
+                        &download.identifier,
+                        &download.url,
+                        &download.sha256_checksum,
+                        Some(bar.clone()),
+                    ) {
+                        cu::error!("{e:?}");
+                        failed.fetch_add(1, Ordering::SeqCst);
+                    }
+                    let finished = finished.fetch_add(1, Ordering::SeqCst) + 1;
+                    cu::progress!(bar, "{finished}/{total}");
+                }
+            });
+        }
+    });
+    bar.done();
+    match failed.into_inner() {
+        0 => Ok(()),
+        x => cu::bail!("failed to download {x} of {total} file(s)"),
+    }
+}
+
 fn client() -> cu::Result<&'static Client> {
     let client: &Result<Client, String> = &CLIENT;
     match client {
@@ -59,6 +59,12 @@ pub fn add_env_assert_once(key: String, value: String) -> cu::Result<()> {
     Ok(())
 }
 
+/// Load the environment assertions in HOME/environment.json, without checking them
+#[inline(always)]
+pub fn env_asserts() -> cu::Result<BTreeMap<String, String>> {
+    load_env_json()
+}
+
 /// Load and check if the current environment matches assertions in HOME/environment.json
 #[inline(always)]
 pub fn init_env() -> cu::Result<()> {
@@ -41,3 +41,24 @@ pub fn lock() -> cu::Result<HomeLock> {
     cu::debug!("acquired home directory lock");
     Ok(HomeLock(file))
 }
+
+/// Check if the lock file exists, but is not held by any running instance of the program
+pub fn has_stale_lock() -> cu::Result<bool> {
+    let path = hmgr::paths::dot_lock();
+    if !path.exists() {
+        return Ok(false);
+    }
+    let file = cu::check!(
+        File::open(&path),
+        "failed to open lock file at '{}'",
+        path.display()
+    )?;
+    if file.try_lock_exclusive().is_err() {
+        // held by another instance
+        return Ok(false);
+    }
+    if let Err(e) = file.unlock() {
+        cu::warn!("failed to unlock lock file: {e:?}");
+    }
+    Ok(true)
+}
@@ -56,6 +56,23 @@ pub fn init_home_path(path: PathBuf) {
     }
 }
 
+/// Initialize the SHAFT_HOME directory path to an empty temporary directory
+/// unique to the current process, for tests. Only the first call creates the directory.
+///
+/// Since the home path is global, tests using it should not use the same files
+#[doc(hidden)]
+pub fn init_test_home() -> &'static Path {
+    static INIT: std::sync::Once = std::sync::Once::new();
+    INIT.call_once(|| {
+        let path = std::env::temp_dir().join(format!("shaft-test-home-{}", std::process::id()));
+        if let Err(e) = cu::fs::make_dir_empty(&path) {
+            panic!("failed to create test home: {e:?}");
+        }
+        init_home_path(path);
+    });
+    home()
+}
+
 pub(crate) fn home() -> &'static Path {
     HOME_PATH
         .get()
@@ -94,6 +111,8 @@ home! {
     dot_lock:              ".lock",
     environment_json:      "environment.json",
     install_cache_json:    "install_cache.json",
+    sync_journal_json:     "sync_journal.json",
+    history_jsonl:         "history.jsonl",
     version_cache_json:    "version_cache.json",
 }
 
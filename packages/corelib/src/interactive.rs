@@ -0,0 +1,40 @@
+//! State of the prompt mode, shared by the CLI and the packages
+//!
+//! With `--yes`, yes/no prompts are answered with yes, and prompts
+//! that have a default take the default. With `--non-interactive` (or `--no-input`),
+//! prompts that have a default take the default, and everything else that
+//! needs a human fails with an error containing how to avoid the prompt.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static YES: AtomicBool = AtomicBool::new(false);
+static NON_INTERACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Set the prompt mode from the `--yes`, `--non-interactive` and `--interactive` flags.
+///
+/// `--interactive` cancels one `--non-interactive`, the same way cu decides if prompts are allowed
+pub fn init(yes: bool, non_interactive: u8, interactive: u8) {
+    YES.store(yes, Ordering::Release);
+    NON_INTERACTIVE.store(non_interactive > interactive, Ordering::Release);
+}
+
+/// If yes/no prompts should be answered with yes automatically
+pub fn is_yes() -> bool {
+    YES.load(Ordering::Acquire)
+}
+
+/// If no human is available to answer prompts, in which case
+/// the default answer should be used when there is one
+pub fn is_unattended() -> bool {
+    is_yes() || NON_INTERACTIVE.load(Ordering::Acquire)
+}
+
+/// Fail with the `fix` hint if input is disabled. Call this before prompting
+/// for something that has no default answer
+pub fn ensure_input(what: &str, fix: &str) -> cu::Result<()> {
+    if !is_unattended() {
+        return Ok(());
+    }
+    cu::hint!("{fix}");
+    cu::bail!("input is required, but prompts are disabled: {what}");
+}
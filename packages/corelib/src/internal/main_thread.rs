@@ -12,106 +12,101 @@ pub fn ensure_main_thread() -> cu::Result<()> {
 }
 
 // Guard invariants:
-// - object always on main thread
-// - ALIVE = true when object is alive
-// - no other reference exists
-macro_rules! main_thread_singleton {
+// - INSTANCE is Some when a Guard is alive
+// - the Guard holds the lock, so no other reference exists
+// - ALIVE is true on the thread that holds the Guard
+//
+// The lock is held for the lifetime of the Guard, so operations on the
+// same singleton (for example, the same external package manager) from
+// different threads are serialized.
+//
+// Singletons declared with `#[session]` also join the package session of the
+// current thread, see `epkg::begin_package_session`
+macro_rules! locked_singleton {
     () => {};
-    (__impl__ __const__ $type:ty, $init:block) => {
-        static mut INSTANCE: $type = $init;
-        impl Guard {
-            /// SAFETY: must be called on main thread
-            #[inline(always)]
-            unsafe fn new_main_thread() -> cu::Result<Self> {
-                let p = &raw mut INSTANCE;
-                // SAFETY: address of static variable can't be null
-                Ok(Self(unsafe { std::ptr::NonNull::new_unchecked(p) }))
-            }
-        }
-    };
-    (__impl__ __non_const__ $type:ty, $init:block) => {
-        static mut INSTANCE: Option<$type> = None;
-        impl Guard {
-            /// SAFETY: must be called on main thread
-            #[inline(always)]
-            unsafe fn new_main_thread() -> cu::Result<Self> {
-                let initialized = {
-                    // SAFETY: short-lived
-                    unsafe{&*(&raw const INSTANCE)}.is_some()
-                };
-                if !initialized {
-                    #[allow(clippy::redundant_closure_call)]
-                    let init_value: cu::Result<$type> = (|| { $init })();
-                    let init_value = init_value?;
-                    {
-                        // SAFETY: we are on main thread, only main thread can call this
-                        unsafe { INSTANCE = Some(init_value) }
-                    }
-                }
-                let inner_ptr = {
-                    std::ptr::NonNull::from(
-                        // SAFETY: short-lived reference
-                        unsafe{&*(&raw const INSTANCE)}.as_ref().unwrap()
-                    )
-                };
-                Ok(Self(inner_ptr))
-            }
-        }
-    };
-    (__impl__ mod $constness:ident, $xxx:ident, $type:ty, $init:block, $($rest:tt)* ) => {
+    (__impl__ mod $init_expr:expr, $xxx:ident, $type:ty, $init:block, $join:block, $($rest:tt)* ) => {
         mod $xxx {
             #[allow(unused)]
             use super::*;
-            static ALIVE: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
-            pub(crate) struct Guard(std::ptr::NonNull<$type>);
+            static INSTANCE: std::sync::Mutex<Option<$type>> = std::sync::Mutex::new($init_expr);
+            thread_local! {
+                static ALIVE: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+            }
+            pub(crate) struct Guard(std::sync::MutexGuard<'static, Option<$type>>);
+            impl Drop for Guard {
+                fn drop(&mut self) {
+                    ALIVE.set(false);
+                }
+            }
             impl std::ops::Deref for Guard {
                 type Target = $type;
                 fn deref(&self) -> &Self::Target {
-                    let p = self.0.as_ptr();
-                    // SAFETY: invariants
-                    unsafe {&*p}
+                    // invariant: initialized when the guard is created
+                    self.0.as_ref().unwrap()
                 }
             }
             impl std::ops::DerefMut for Guard {
                 fn deref_mut(&mut self) -> &mut Self::Target {
-                    let p = self.0.as_ptr();
-                    // SAFETY: invariants
-                    unsafe {&mut *p}
+                    // invariant: initialized when the guard is created
+                    self.0.as_mut().unwrap()
                 }
             }
-            // invariant: object always on main thread
-            $crate::internal::main_thread_singleton!(__impl__ $constness $type, $init);
-            impl Drop for Guard {
-                fn drop(&mut self) {
-                    // relaxed is fine because we check it's only used on the main thread
-                    ALIVE.store(false, std::sync::atomic::Ordering::Relaxed);
-                }
-            }
-            /// Get instance - will error if not on the main thread or another Guard is alive
+            /// Get instance - will block until Guards alive on other threads are dropped,
+            /// and will error if another Guard is alive on the current thread
             pub fn instance() -> cu::Result<Guard> {
                 use cu::Context as _;
-                crate::internal::ensure_main_thread().context(concat!(stringify!($xxx), " instance can only be accessed on the main thread"))?;
-                // relaxed is fine because we check it's only used on the main thread
+                // locking again on the same thread would deadlock
                 cu::ensure!(
-                    ALIVE.compare_exchange(
-                        false, true,
-                        std::sync::atomic::Ordering::Relaxed,
-                        std::sync::atomic::Ordering::Relaxed,
-                    ).is_ok(),
+                    !ALIVE.get(),
                     concat!("another guard of ", stringify!($xxx), " is alive")
                 )?;
-                // SAFETY: ensured on main thread
-                let result = unsafe { Guard::new_main_thread() };
-                result.context(concat!("failed to initialize main thread singleton: ", stringify!($xxx)))
+                $join
+                // the state is only a cache, so it's fine to keep using it
+                // if another thread panicked while holding the lock
+                let mut guard = INSTANCE.lock().unwrap_or_else(|e| e.into_inner());
+                if guard.is_none() {
+                    #[allow(clippy::redundant_closure_call)]
+                    let init_value: cu::Result<$type> = (|| { $init })();
+                    let init_value = init_value.context(concat!("failed to initialize singleton: ", stringify!($xxx)))?;
+                    *guard = Some(init_value);
+                }
+                ALIVE.set(true);
+                Ok(Guard(guard))
             }
         }
-        $crate::internal::main_thread_singleton!($($rest)*);
+        $crate::internal::locked_singleton!($($rest)*);
+    };
+    (#[session] const $xxx:ident = $type:ident :: $init_ident:ident (); $($rest:tt)* ) => {
+        $crate::internal::locked_singleton!(__impl__ mod Some($type::$init_ident()), $xxx, $type, { Ok($type::$init_ident()) }, { $crate::epkg::session::join_session(); }, $($rest)*);
     };
     (const $xxx:ident = $type:ident :: $init_ident:ident (); $($rest:tt)* ) => {
-        $crate::internal::main_thread_singleton!(__impl__ mod __const__, $xxx, $type, { $type::$init_ident() }, $($rest)*);
+        $crate::internal::locked_singleton!(__impl__ mod Some($type::$init_ident()), $xxx, $type, { Ok($type::$init_ident()) }, {}, $($rest)*);
     };
     (let $xxx:ident = $type:ident :: $init_ident:ident (); $($rest:tt)* ) => {
-        $crate::internal::main_thread_singleton!(__impl__ mod __non_const__, $xxx, $type, { $type::$init_ident() }, $($rest)*);
+        $crate::internal::locked_singleton!(__impl__ mod None, $xxx, $type, { $type::$init_ident() }, {}, $($rest)*);
     };
 }
-pub(crate) use main_thread_singleton;
+pub(crate) use locked_singleton;
+
+#[cfg(test)]
+mod test {
+    pub(crate) struct Counter(usize);
+    impl Counter {
+        const fn new() -> Self {
+            Self(0)
+        }
+    }
+    crate::internal::locked_singleton! {
+        const counter = Counter::new();
+    }
+
+    #[test]
+    fn test_locked_singleton_reentry() {
+        let mut guard = counter::instance().unwrap();
+        guard.0 += 1;
+        // would deadlock if it blocked
+        assert!(counter::instance().is_err());
+        drop(guard);
+        assert_eq!(counter::instance().unwrap().0, 1);
+    }
+}
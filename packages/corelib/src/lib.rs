@@ -2,6 +2,8 @@
 pub mod epkg;
 /// Home Manager. Manages contents in the SHAFT_HOME directory
 pub mod hmgr;
+/// Prompt mode when running unattended
+pub mod interactive;
 /// Operating/File System
 pub mod opfs;
 
@@ -1,5 +1,5 @@
 use std::path::{Path, PathBuf};
-use std::sync::LazyLock;
+use std::sync::{LazyLock, Mutex};
 use std::time::Duration;
 
 use cu::pre::*;
@@ -69,7 +69,11 @@ pub fn sudo_path(path: &Path, reason: &str) -> cu::Result<cu::Command<(), (), ()
     sudo_path_name(path, name, reason)
 }
 
+/// Only one sudo prompt is shown at a time, when packages are synced concurrently
+static PROMPT_LOCK: Mutex<()> = Mutex::new(());
+
 fn sudo_path_name(path: &Path, name: &str, reason: &str) -> cu::Result<cu::Command<(), (), ()>> {
+    let prompt_lock = PROMPT_LOCK.lock().unwrap_or_else(|e| e.into_inner());
     #[cfg(not(windows))]
     {
         validate_credential(path, reason)?;
@@ -100,6 +104,7 @@ fn sudo_path_name(path: &Path, name: &str, reason: &str) -> cu::Result<cu::Comma
         .or_cancel()
         .run()?;
     }
+    drop(prompt_lock);
     let mut command = which_sudo()?.command();
     if !name.is_empty() {
         command = command.name(name);
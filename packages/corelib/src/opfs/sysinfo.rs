@@ -13,7 +13,7 @@ impl State {
     }
 }
 
-internal::main_thread_singleton! {
+internal::locked_singleton! {
     let system = State::new();
 }
 
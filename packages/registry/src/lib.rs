@@ -6,7 +6,7 @@ pub use metadata::core::CONFIG_VERSION as CORE_VERSION;
 #[path = "./packages.gen.rs"]
 #[rustfmt::skip]
 mod _gen;
-pub use _gen::{BinId, PkgId};
+pub use _gen::{BinId, GroupId, PkgId};
 
 mod util;
 pub use util::*;
@@ -19,7 +19,7 @@ pub(crate) mod pre {
     pub(crate) use corelib::hmgr::config::ConfigDef;
     #[allow(unused)]
     pub(crate) use corelib::{
-        Version, VersionCache, bin_name, command_output, epkg, hmgr, jsexe, opfs,
+        Version, VersionCache, bin_name, command_output, epkg, hmgr, interactive, jsexe, opfs,
     };
     pub(crate) use cu::pre::*;
     pub(crate) use enumset::{EnumSet, enum_set};
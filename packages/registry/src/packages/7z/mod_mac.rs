@@ -12,7 +12,7 @@ pub fn verify(_: &Context) -> cu::Result<Verified> {
     version::check()
 }
 pub fn download(ctx: &Context) -> cu::Result<()> {
-    hmgr::download_file("7z.txz", download_url(), metadata::_7z::SHA(), ctx.bar())?;
+    ctx.download_file("7z.txz", download_url(), metadata::_7z::SHA())?;
     Ok(())
 }
 pub fn install(ctx: &Context) -> cu::Result<()> {
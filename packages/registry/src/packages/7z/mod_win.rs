@@ -14,17 +14,12 @@ pub fn verify(ctx: &Context) -> cu::Result<Verified> {
         // ensure the uninstaller exists in a good installation
         // so it can be uninstalled
         cu::debug!("7z uninstaller does not exist");
-        return Ok(Verified::NotUpToDate);
+        return Ok(Verified::NotUpToDate(None));
     }
     version::check()
 }
 pub fn download(ctx: &Context) -> cu::Result<()> {
-    hmgr::download_file(
-        "7z-installer.exe",
-        download_url(),
-        metadata::_7z::SHA(),
-        ctx.bar(),
-    )?;
+    ctx.download_file("7z-installer.exe", download_url(), metadata::_7z::SHA())?;
     Ok(())
 }
 pub fn install(ctx: &Context) -> cu::Result<()> {
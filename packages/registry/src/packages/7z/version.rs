@@ -17,9 +17,9 @@ pub fn check() -> cu::Result<Verified> {
             };
             version = version2;
         }
-        check_outdated!(version, metadata[_7z]::VERSION);
-        return Ok(Verified::UpToDate);
+        let version_info = check_outdated!(version, metadata[_7z]::VERSION);
+        return Ok(Verified::UpToDate(Some(version_info)));
     }
     cu::warn!("failed to parse current version for '7z'");
-    Ok(Verified::NotUpToDate)
+    Ok(Verified::NotUpToDate(None))
 }
@@ -8,12 +8,12 @@ pub fn verify(_: &Context) -> cu::Result<Verified> {
     check_in_shaft!("bun");
     check_in_shaft!("bunx");
     let version = command_output!("bun", ["--version"]);
-    check_outdated!(version.trim(), metadata[bun]::VERSION);
-    Ok(Verified::UpToDate)
+    let version_info = check_outdated!(version.trim(), metadata[bun]::VERSION);
+    Ok(Verified::UpToDate(Some(version_info)))
 }
 pub fn download(ctx: &Context) -> cu::Result<()> {
     let file_name = bun_file_name()?;
-    hmgr::download_file(file_name, bun_url()?, metadata::bun::SHA(), ctx.bar())?;
+    ctx.download_file(file_name, bun_url()?, metadata::bun::SHA())?;
     Ok(())
 }
 
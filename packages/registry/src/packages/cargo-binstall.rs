@@ -6,8 +6,8 @@ register_binaries!("cargo-binstall");
 
 pub fn verify(_: &Context) -> cu::Result<Verified> {
     let info = check_cargo!("cargo-binstall");
-    check_outdated!(&info.version, metadata[cargo_binstall]::VERSION);
-    Ok(Verified::UpToDate)
+    let version_info = check_outdated!(&info.version, metadata[cargo_binstall]::VERSION);
+    Ok(Verified::UpToDate(Some(version_info)))
 }
 
 pub fn install(ctx: &Context) -> cu::Result<()> {
@@ -20,7 +20,7 @@ pub fn verify(ctx: &Context) -> cu::Result<Verified> {
     let info = ClangInfo::parse(&output);
     if info.version.is_empty() {
         cu::warn!("failed to determine clang version");
-        return Ok(Verified::NotUpToDate);
+        return Ok(Verified::NotUpToDate(None));
     }
     if cfg!(windows) {
         let expected_install_dir = ctx.install_dir().join("llvm\\bin");
@@ -31,8 +31,8 @@ pub fn verify(ctx: &Context) -> cu::Result<Verified> {
             );
         }
     }
-    check_outdated!(&info.version, metadata[clang]::LLVM_VERSION);
-    Ok(Verified::UpToDate)
+    let version_info = check_outdated!(&info.version, metadata[clang]::LLVM_VERSION);
+    Ok(Verified::UpToDate(Some(version_info)))
 }
 
 struct ClangInfo {
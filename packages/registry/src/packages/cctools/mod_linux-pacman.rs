@@ -15,7 +15,7 @@ binary_dependencies!(Python);
 pub fn verify(_: &Context) -> cu::Result<Verified> {
     let v = check_pacman!("gcc");
     let v = v.split_once('+').map(|x| x.0).unwrap_or(&v);
-    check_outdated!(v, metadata[gnucc::gcc]::VERSION);
+    let version_info = check_outdated!(v, metadata[gnucc::gcc]::VERSION);
 
     let v = check_pacman!("binutils");
     let v = v.split_once('+').map(|x| x.0).unwrap_or(&v);
@@ -29,7 +29,7 @@ pub fn verify(_: &Context) -> cu::Result<Verified> {
     check_outdated!(&v, metadata[clang]::LLVM_VERSION);
     let v = check_pacman!("lldb");
     check_outdated!(&v, metadata[clang]::LLVM_VERSION);
-    Ok(Verified::UpToDate)
+    Ok(Verified::UpToDate(Some(version_info)))
 }
 
 pub fn install(ctx: &Context) -> cu::Result<()> {
@@ -50,16 +50,15 @@ binary_dependencies!(_7z, Scalar, Python, Cmake);
 mod clang;
 pub fn verify(ctx: &Context) -> cu::Result<Verified> {
     check_verified!(clang::verify(ctx)?);
-    Ok(Verified::UpToDate)
+    Ok(Verified::UpToDate(None))
 }
 
 pub fn download(ctx: &Context) -> cu::Result<()> {
-    hmgr::download_file("llvm.txz", llvm_url(), metadata::clang::SHA(), ctx.bar())?;
-    hmgr::download_file(
+    ctx.download_file("llvm.txz", llvm_url(), metadata::clang::SHA())?;
+    ctx.download_file(
         "llvm-mingw.zip",
         llvm_mingw_url(),
         metadata::llvm_mingw::SHA(),
-        ctx.bar(),
     )?;
     Ok(())
 }
@@ -4,8 +4,8 @@ register_binaries!("cmake");
 
 pub fn verify(_: &Context) -> cu::Result<Verified> {
     let v = check_pacman!("cmake");
-    check_outdated!(&v, metadata[cmake]::VERSION);
-    Ok(Verified::UpToDate)
+    let version_info = check_outdated!(&v, metadata[cmake]::VERSION);
+    Ok(Verified::UpToDate(Some(version_info)))
 }
 pub fn install(ctx: &Context) -> cu::Result<()> {
     epkg::pacman::install("cmake", ctx.bar_ref())?;
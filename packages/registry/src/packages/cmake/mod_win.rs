@@ -11,23 +11,23 @@ pub fn verify(_: &Context) -> cu::Result<Verified> {
     let mut v = v.split_whitespace();
     if v.next() != Some("cmake") {
         cu::warn!("failed to parse cmake version");
-        return Ok(Verified::NotUpToDate);
+        return Ok(Verified::NotUpToDate(None));
     }
     if v.next() != Some("version") {
         cu::warn!("failed to parse cmake version");
-        return Ok(Verified::NotUpToDate);
+        return Ok(Verified::NotUpToDate(None));
     }
     let Some(v) = v.next() else {
         cu::warn!("failed to parse cmake version");
-        return Ok(Verified::NotUpToDate);
+        return Ok(Verified::NotUpToDate(None));
     };
-    check_outdated!(v, metadata[cmake]::VERSION);
+    let version_info = check_outdated!(v, metadata[cmake]::VERSION);
 
-    Ok(Verified::UpToDate)
+    Ok(Verified::UpToDate(Some(version_info)))
 }
 
 pub fn download(ctx: &Context) -> cu::Result<()> {
-    hmgr::download_file("cmake.zip", cmake_url(), metadata::cmake::SHA(), ctx.bar())?;
+    ctx.download_file("cmake.zip", cmake_url(), metadata::cmake::SHA())?;
     Ok(())
 }
 
@@ -2,7 +2,7 @@ use crate::pre::*;
 
 pub fn verify(_: &Context) -> cu::Result<Verified> {
     corelib::check_requirements()?;
-    Ok(Verified::UpToDate)
+    Ok(Verified::UpToDate(None))
 }
 
 pub fn install(_: &Context) -> cu::Result<()> {
@@ -3,8 +3,8 @@ use crate::pre::*;
 pub fn verify() -> cu::Result<Verified> {
     check_in_shaft!("eza");
     let version = get_version()?;
-    check_outdated!(&version, metadata[coreutils::eza]::VERSION);
-    Ok(Verified::UpToDate)
+    let version_info = check_outdated!(&version, metadata[coreutils::eza]::VERSION);
+    Ok(Verified::UpToDate(Some(version_info)))
 }
 
 fn get_version() -> cu::Result<String> {
@@ -19,7 +19,7 @@ fn get_version() -> cu::Result<String> {
 }
 
 pub fn install(ctx: &Context) -> cu::Result<()> {
-    if let Ok(Verified::UpToDate) = verify() {
+    if let Ok(Verified::UpToDate(_)) = verify() {
         return Ok(());
     }
     let install_dir = cu::path!((ctx.install_dir()) / "eza").into_utf8()?;
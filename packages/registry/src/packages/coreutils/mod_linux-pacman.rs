@@ -27,7 +27,7 @@ pub fn verify(_: &Context) -> cu::Result<Verified> {
     check_pacman!("reflector");
 
     let v = check_pacman!("bash");
-    check_outdated!(&v, metadata[coreutils::bash]::VERSION);
+    let version_info = check_outdated!(&v, metadata[coreutils::bash]::VERSION);
     let v = check_pacman!("bash-completion");
     check_outdated!(&v, metadata[coreutils::bash_cmp]::VERSION);
 
@@ -40,7 +40,7 @@ pub fn verify(_: &Context) -> cu::Result<Verified> {
     check_outdated!(&v, metadata[coreutils::yay]::VERSION);
 
     check_config_version_cache!(common::ALIAS_VERSION);
-    Ok(Verified::UpToDate)
+    Ok(Verified::UpToDate(Some(version_info)))
 }
 
 pub fn install(ctx: &Context) -> cu::Result<()> {
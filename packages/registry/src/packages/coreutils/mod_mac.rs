@@ -12,7 +12,7 @@ pub fn verify(_: &Context) -> cu::Result<Verified> {
     check_in_path!("diff");
 
     check_config_version_cache!(common::ALIAS_VERSION);
-    Ok(Verified::UpToDate)
+    Ok(Verified::UpToDate(None))
 }
 
 pub fn install(ctx: &Context) -> cu::Result<()> {
@@ -87,7 +87,7 @@ pub fn verify(_: &Context) -> cu::Result<Verified> {
     check_in_shaft!("gzip");
 
     check_config_version_cache!(common::ALIAS_VERSION);
-    Ok(Verified::UpToDate)
+    Ok(Verified::UpToDate(None))
 }
 
 fn check_sbin_path() -> cu::Result<()> {
@@ -130,8 +130,8 @@ fn check_sbin_path() -> cu::Result<()> {
 fn verify_coreutils_version() -> cu::Result<Verified> {
     check_in_shaft!("coreutils");
     let v = get_coreutils_version()?;
-    check_outdated!(&v, metadata[coreutils::ms_coreutils]::VERSION);
-    Ok(Verified::UpToDate)
+    let version_info = check_outdated!(&v, metadata[coreutils::ms_coreutils]::VERSION);
+    Ok(Verified::UpToDate(Some(version_info)))
 }
 
 fn get_coreutils_version() -> cu::Result<String> {
@@ -142,11 +142,10 @@ fn get_coreutils_version() -> cu::Result<String> {
 }
 
 pub fn download(ctx: &Context) -> cu::Result<()> {
-    hmgr::download_file(
+    ctx.download_file(
         "coreutils.zip",
         download_url(),
         metadata::coreutils::ms_coreutils::SHA(),
-        ctx.bar(),
     )?;
     Ok(())
 }
@@ -173,7 +172,7 @@ pub fn install(ctx: &Context) -> cu::Result<()> {
     let artifact_name = artifact_name();
     let coreutils_bin = cu::path!(&coreutils_dir / (&artifact_name) / bin_name!("coreutils"));
     if coreutils_bin.exists()
-        && let Ok(Verified::UpToDate) = verify_coreutils_version()
+        && let Ok(Verified::UpToDate(_)) = verify_coreutils_version()
     {
         return Ok(());
     }
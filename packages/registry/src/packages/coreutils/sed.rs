@@ -2,11 +2,11 @@ use crate::pre::*;
 
 pub fn verify() -> cu::Result<Verified> {
     let v = check_cargo!("sed");
-    check_outdated!(&v.version, metadata[coreutils::uutils_sed]::VERSION);
-    Ok(Verified::UpToDate)
+    let version_info = check_outdated!(&v.version, metadata[coreutils::uutils_sed]::VERSION);
+    Ok(Verified::UpToDate(Some(version_info)))
 }
 pub fn install(ctx: &Context) -> cu::Result<()> {
-    if let Ok(Verified::UpToDate) = verify() {
+    if let Ok(Verified::UpToDate(_)) = verify() {
         return Ok(());
     }
     epkg::cargo::binstall("sed", ctx.bar_ref())?;
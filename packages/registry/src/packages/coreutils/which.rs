@@ -2,11 +2,11 @@ use crate::pre::*;
 
 pub fn verify() -> cu::Result<Verified> {
     let v = check_cargo!("which" in crate "shutil-which");
-    check_outdated!(&v.version, metadata[shellutils::which]::VERSION);
-    Ok(Verified::UpToDate)
+    let version_info = check_outdated!(&v.version, metadata[shellutils::which]::VERSION);
+    Ok(Verified::UpToDate(Some(version_info)))
 }
 pub fn install(ctx: &Context) -> cu::Result<()> {
-    if let Ok(Verified::UpToDate) = verify() {
+    if let Ok(Verified::UpToDate(_)) = verify() {
         return Ok(());
     }
     hmgr::repo::ensure_checkout()?;
@@ -6,7 +6,7 @@ version_cache!(static KBD_ULEDS_VERSION = metadata::framework16::kbd_uleds::COMM
 pub fn verify(_: &Context) -> cu::Result<Verified> {
     check_pacman!("fw16-kbd-uleds-git");
     check_config_version_cache!(KBD_ULEDS_VERSION);
-    Ok(Verified::UpToDate)
+    Ok(Verified::UpToDate(None))
 }
 pub fn install(ctx: &Context) -> cu::Result<()> {
     let install_dir = ctx.install_dir();
@@ -8,9 +8,9 @@ binary_dependencies!(Git);
 
 pub fn verify(_: &Context) -> cu::Result<Verified> {
     let v = check_cargo!("delta" in crate "git-delta");
-    check_outdated!(&v.version, metadata[git::delta]::VERSION);
+    let version_info = check_outdated!(&v.version, metadata[git::delta]::VERSION);
     check_config_version_cache!(VERSION);
-    Ok(Verified::UpToDate)
+    Ok(Verified::UpToDate(Some(version_info)))
 }
 pub fn install(ctx: &Context) -> cu::Result<()> {
     epkg::cargo::install("git-delta", None, ctx.bar_ref())?;
@@ -12,7 +12,7 @@ pub fn verify(_: &Context) -> cu::Result<Verified> {
     check_verified!(verify_git_installed()?);
     check_config_version_cache!(ALIAS_VERSION);
     check_in_path!("bash");
-    Ok(Verified::UpToDate)
+    Ok(Verified::UpToDate(None))
 }
 
 // installing git is slow since winget doesn't have a normal "--needed" mode
@@ -20,11 +20,11 @@ fn verify_git_installed() -> cu::Result<Verified> {
     check_in_path!("git");
     check_in_path!("scalar");
     check_verified!(version::verify(true)?);
-    Ok(Verified::UpToDate)
+    Ok(Verified::UpToDate(None))
 }
 
 pub fn install(ctx: &Context) -> cu::Result<()> {
-    if let Ok(Verified::UpToDate) = verify_git_installed() {
+    if let Ok(Verified::UpToDate(_)) = verify_git_installed() {
         return Ok(());
     }
     opfs::ensure_terminated("git.exe")?;
@@ -6,13 +6,13 @@ pub fn verify(require_vfs: bool) -> cu::Result<Verified> {
         let Some(version) = line.strip_prefix("git version ") else {
             continue;
         };
-        check_outdated!(version, metadata[git]::VERSION);
+        let version_info = check_outdated!(version, metadata[git]::VERSION);
         if require_vfs && !version.contains("vfs") {
             cu::bail!(
                 "current 'git' is not the vfs version (microsoft.git); please uninstall it or use the 'system-git' package"
             );
         }
-        return Ok(Verified::UpToDate);
+        return Ok(Verified::UpToDate(Some(version_info)));
     }
     cu::bail!("failed to get git version from output: {stdout}");
 }
@@ -5,8 +5,8 @@ binary_dependencies!(Java);
 
 pub fn verify(_: &Context) -> cu::Result<Verified> {
     let v = check_cargo!("gradlew" in crate "gradle-wrapper-cli");
-    check_outdated!(&v.version, metadata[gradle]::VERSION);
-    Ok(Verified::UpToDate)
+    let version_info = check_outdated!(&v.version, metadata[gradle]::VERSION);
+    Ok(Verified::UpToDate(Some(version_info)))
 }
 
 pub fn install(ctx: &Context) -> cu::Result<()> {
@@ -9,8 +9,8 @@ pub fn verify(_: &Context) -> cu::Result<Verified> {
     check_in_path!("javac");
 
     let version = current_version()?;
-    check_outdated!(&version, metadata[java]::VERSION);
-    Ok(Verified::UpToDate)
+    let version_info = check_outdated!(&version, metadata[java]::VERSION);
+    Ok(Verified::UpToDate(Some(version_info)))
 }
 
 fn current_version() -> cu::Result<String> {
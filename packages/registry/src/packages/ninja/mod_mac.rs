@@ -6,8 +6,8 @@ register_binaries!("ninja");
 
 pub fn verify(_: &Context) -> cu::Result<Verified> {
     let v = check_homebrew!("ninja");
-    check_outdated!(&v, metadata[ninja]::VERSION);
-    Ok(Verified::UpToDate)
+    let version_info = check_outdated!(&v, metadata[ninja]::VERSION);
+    Ok(Verified::UpToDate(Some(version_info)))
 }
 
 pub fn install(ctx: &Context) -> cu::Result<()> {
@@ -7,11 +7,11 @@ register_binaries!("ninja");
 pub fn verify(_: &Context) -> cu::Result<Verified> {
     check_in_shaft!("ninja");
     let v = command_output!("ninja", ["--version"]);
-    check_outdated!(v.trim(), metadata[ninja]::VERSION);
-    Ok(Verified::UpToDate)
+    let version_info = check_outdated!(v.trim(), metadata[ninja]::VERSION);
+    Ok(Verified::UpToDate(Some(version_info)))
 }
 pub fn download(ctx: &Context) -> cu::Result<()> {
-    hmgr::download_file("ninja.zip", ninja_url(), metadata::ninja::SHA(), ctx.bar())?;
+    ctx.download_file("ninja.zip", ninja_url(), metadata::ninja::SHA())?;
     Ok(())
 }
 
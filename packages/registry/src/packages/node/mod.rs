@@ -12,16 +12,11 @@ pub fn verify(_: &Context) -> cu::Result<Verified> {
     check_in_shaft!("pnpm");
     check_in_shaft!("yarn");
     check_config_version_cache!(ALIAS_VERSION);
-    Ok(Verified::UpToDate)
+    Ok(Verified::UpToDate(None))
 }
 
 pub fn download(ctx: &Context) -> cu::Result<()> {
-    hmgr::download_file(
-        volta_file_name(),
-        volta_url()?,
-        metadata::volta::SHA(),
-        ctx.bar(),
-    )?;
+    ctx.download_file(volta_file_name(), volta_url()?, metadata::volta::SHA())?;
     Ok(())
 }
 pub fn install(ctx: &Context) -> cu::Result<()> {
@@ -3,14 +3,14 @@ use crate::pre::*;
 
 register_binaries!("vi", "vim", "nvim");
 binary_dependencies!(
-    _7z, TreeSitter, Git,      // various
-    Clang,    // compile tree sitter
-    Diff,     // undo tree
-    Websocat, // yank to host
-    Fzf,      // various
-    Rg,       // various
-    Python,   // setup, various
-    Node      // installing lsp
+    _7z, TreeSitter, Git, // various
+    Clang,                // compile tree sitter
+    Diff,                 // undo tree
+    Websocat,             // yank to host
+    Fzf,                  // various
+    Rg,                   // various
+    Python >= "3.11",     // setup, various
+    Node >= "20"          // installing lsp
 );
 config_dependencies!(Shellutils); // vinvim
 
@@ -22,16 +22,16 @@ pub fn verify(_: &Context) -> cu::Result<Verified> {
     let version_line = stdout.lines().next().unwrap_or("");
     let Some(version) = version_line.strip_prefix("NVIM v") else {
         cu::warn!("nvim --version returned unexpected output: {stdout}");
-        return Ok(Verified::NotUpToDate);
+        return Ok(Verified::NotUpToDate(None));
     };
-    check_outdated!(version, metadata[nvim]::VERSION);
+    let version_info = check_outdated!(version, metadata[nvim]::VERSION);
     check_config_version_cache!(CFG);
-    Ok(Verified::UpToDate)
+    Ok(Verified::UpToDate(Some(version_info)))
 }
 
 pub fn download(ctx: &Context) -> cu::Result<()> {
     let file_name = nvim_file_name()?;
-    hmgr::download_file(file_name, nvim_url()?, metadata::nvim::SHA(), ctx.bar())?;
+    ctx.download_file(file_name, nvim_url()?, metadata::nvim::SHA())?;
     Ok(())
 }
 
@@ -32,12 +32,12 @@ pub fn verify(ctx: &Context) -> cu::Result<Verified> {
             "$PSVersionTable.PSVersion.ToString()"
         ]
     );
-    check_outdated!(version.trim(), metadata[pwsh]::VERSION);
-    Ok(Verified::UpToDate)
+    let version_info = check_outdated!(version.trim(), metadata[pwsh]::VERSION);
+    Ok(Verified::UpToDate(Some(version_info)))
 }
 
 pub fn download(ctx: &Context) -> cu::Result<()> {
-    hmgr::download_file("pwsh.zip", download_url(), metadata::pwsh::SHA(), ctx.bar())?;
+    ctx.download_file("pwsh.zip", download_url(), metadata::pwsh::SHA())?;
     Ok(())
 }
 
@@ -20,8 +20,8 @@ pub fn verify(_: &Context) -> cu::Result<Verified> {
         check_in_path!("python");
     }
     let v = check_cargo!("uv");
-    check_outdated!(&v.version, metadata[uv]::VERSION);
-    Ok(Verified::UpToDate)
+    let version_info = check_outdated!(&v.version, metadata[uv]::VERSION);
+    Ok(Verified::UpToDate(Some(version_info)))
 }
 
 pub fn install(ctx: &Context) -> cu::Result<()> {
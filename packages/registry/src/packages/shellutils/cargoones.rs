@@ -2,7 +2,7 @@ use crate::pre::*;
 
 pub fn verify() -> cu::Result<Verified> {
     let v = check_cargo!("bat");
-    check_outdated!(&v.version, metadata[bat]::VERSION);
+    let version_info = check_outdated!(&v.version, metadata[bat]::VERSION);
     let v = check_cargo!("dust" in crate "du-dust");
     check_outdated!(&v.version, metadata[dust]::VERSION);
     let v = check_cargo!("fd" in crate "fd-find");
@@ -13,7 +13,7 @@ pub fn verify() -> cu::Result<Verified> {
     check_outdated!(&v.version, metadata[websocat]::VERSION);
     let v = check_cargo!("zoxide");
     check_outdated!(&v.version, metadata[zoxide]::VERSION);
-    Ok(Verified::UpToDate)
+    Ok(Verified::UpToDate(Some(version_info)))
 }
 
 pub fn install(ctx: &Context) -> cu::Result<()> {
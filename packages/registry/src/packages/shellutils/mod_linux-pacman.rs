@@ -26,7 +26,7 @@ pub fn verify(_: &Context) -> cu::Result<Verified> {
     )?;
 
     let v = check_pacman!("curl");
-    check_outdated!(&v, metadata[curl]::VERSION);
+    let version_info = check_outdated!(&v, metadata[curl]::VERSION);
     let v = check_pacman!("wget");
     check_outdated!(&v, metadata[wget]::VERSION);
     let v = check_pacman!("fzf");
@@ -39,7 +39,7 @@ pub fn verify(_: &Context) -> cu::Result<Verified> {
     check_verified!(shutil::verify()?);
 
     check_config_version_cache!(common::ALIAS_VERSION);
-    Ok(Verified::UpToDate)
+    Ok(Verified::UpToDate(Some(version_info)))
 }
 
 pub fn download(ctx: &Context) -> cu::Result<()> {
@@ -24,7 +24,7 @@ pub fn verify(_: &Context) -> cu::Result<Verified> {
     check_in_path!("curl");
 
     let v = check_homebrew!("wget");
-    check_outdated!(&v, metadata[wget]::VERSION);
+    let version_info = check_outdated!(&v, metadata[wget]::VERSION);
     let v = check_homebrew!("fzf");
     check_outdated!(&v, metadata[fzf]::VERSION);
     let v = check_homebrew!("jq");
@@ -35,7 +35,7 @@ pub fn verify(_: &Context) -> cu::Result<Verified> {
     check_verified!(shutil::verify()?);
 
     check_config_version_cache!(common::ALIAS_VERSION);
-    Ok(Verified::UpToDate)
+    Ok(Verified::UpToDate(Some(version_info)))
 }
 
 pub fn download(ctx: &Context) -> cu::Result<()> {
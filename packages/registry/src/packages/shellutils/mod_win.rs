@@ -30,14 +30,14 @@ pub fn verify(_: &Context) -> cu::Result<Verified> {
 
     check_in_shaft!("wget");
     let v = wget::version_check()?;
-    if v != Verified::UpToDate {
+    if !matches!(v, Verified::UpToDate(_)) {
         return Ok(v);
     }
 
     check_in_shaft!("fzf");
     let v = command_output!("fzf", ["--version"]);
     let v = v.split_once(' ').map(|x| x.0).unwrap_or(&v);
-    check_outdated!(v.trim(), metadata[fzf]::VERSION);
+    let version_info = check_outdated!(v.trim(), metadata[fzf]::VERSION);
 
     check_in_shaft!("jq");
     let v = command_output!("jq", ["--version"]);
@@ -49,17 +49,12 @@ pub fn verify(_: &Context) -> cu::Result<Verified> {
     check_verified!(shutil::verify()?);
 
     check_config_version_cache!(common::ALIAS_VERSION);
-    Ok(Verified::UpToDate)
+    Ok(Verified::UpToDate(Some(version_info)))
 }
 pub fn download(ctx: &Context) -> cu::Result<()> {
-    hmgr::download_file(
-        "wget.7z",
-        metadata::wget::URL,
-        metadata::wget::SHA,
-        ctx.bar(),
-    )?;
-    hmgr::download_file("fzf.zip", fzf_url(), metadata::fzf::SHA(), ctx.bar())?;
-    hmgr::download_file("jq.exe", jq_url(), metadata::jq::SHA, ctx.bar())?;
+    ctx.download_file("wget.7z", metadata::wget::URL, metadata::wget::SHA)?;
+    ctx.download_file("fzf.zip", fzf_url(), metadata::fzf::SHA())?;
+    ctx.download_file("jq.exe", jq_url(), metadata::jq::SHA)?;
     task::download(ctx)?;
     Ok(())
 }
@@ -8,46 +8,46 @@ pub fn verify() -> cu::Result<Verified> {
         check_verified!(verify_vipath()?);
         check_verified!(verify_wsclip()?);
     }
-    Ok(Verified::UpToDate)
+    Ok(Verified::UpToDate(None))
 }
 
 pub fn verify_n() -> cu::Result<Verified> {
     let v = check_cargo!("n" in crate "shutil-n");
-    check_outdated!(&v.version, metadata[shellutils::n]::VERSION);
-    Ok(Verified::UpToDate)
+    let version_info = check_outdated!(&v.version, metadata[shellutils::n]::VERSION);
+    Ok(Verified::UpToDate(Some(version_info)))
 }
 pub fn verify_viopen() -> cu::Result<Verified> {
     let v = check_cargo!("viopen" in crate "shutil-viopen");
-    check_outdated!(&v.version, metadata[shellutils::viopen]::VERSION);
-    Ok(Verified::UpToDate)
+    let version_info = check_outdated!(&v.version, metadata[shellutils::viopen]::VERSION);
+    Ok(Verified::UpToDate(Some(version_info)))
 }
 pub fn verify_lfmt() -> cu::Result<Verified> {
     let v = check_cargo!("lfmt" in crate "shutil-lfmt");
-    check_outdated!(&v.version, metadata[shellutils::lfmt]::VERSION);
-    Ok(Verified::UpToDate)
+    let version_info = check_outdated!(&v.version, metadata[shellutils::lfmt]::VERSION);
+    Ok(Verified::UpToDate(Some(version_info)))
 }
 pub fn verify_vipath() -> cu::Result<Verified> {
     let v = check_cargo!("vipath" in crate "shutil-vipath");
-    check_outdated!(&v.version, metadata[shellutils::vipath]::VERSION);
-    Ok(Verified::UpToDate)
+    let version_info = check_outdated!(&v.version, metadata[shellutils::vipath]::VERSION);
+    Ok(Verified::UpToDate(Some(version_info)))
 }
 pub fn verify_wsclip() -> cu::Result<Verified> {
     let v = check_cargo!("wsclip" in crate "shutil-wsclip");
-    check_outdated!(&v.version, metadata[shellutils::wsclip]::VERSION);
-    Ok(Verified::UpToDate)
+    let version_info = check_outdated!(&v.version, metadata[shellutils::wsclip]::VERSION);
+    Ok(Verified::UpToDate(Some(version_info)))
 }
 
 pub fn install(ctx: &Context) -> cu::Result<()> {
     let mut need_install = false;
-    let need_n = !matches!(verify_n(), Ok(Verified::UpToDate));
+    let need_n = !matches!(verify_n(), Ok(Verified::UpToDate(_)));
     need_install = need_install || need_n;
-    let need_viopen = !matches!(verify_viopen(), Ok(Verified::UpToDate));
+    let need_viopen = !matches!(verify_viopen(), Ok(Verified::UpToDate(_)));
     need_install = need_install || need_viopen;
-    let need_lfmt = !matches!(verify_lfmt(), Ok(Verified::UpToDate));
+    let need_lfmt = !matches!(verify_lfmt(), Ok(Verified::UpToDate(_)));
     need_install = need_install || need_lfmt;
-    let need_vipath = cfg!(windows) && !matches!(verify_vipath(), Ok(Verified::UpToDate));
+    let need_vipath = cfg!(windows) && !matches!(verify_vipath(), Ok(Verified::UpToDate(_)));
     need_install = need_install || need_vipath;
-    let need_wsclip = cfg!(windows) && !matches!(verify_wsclip(), Ok(Verified::UpToDate));
+    let need_wsclip = cfg!(windows) && !matches!(verify_wsclip(), Ok(Verified::UpToDate(_)));
     need_install = need_install || need_wsclip;
 
     if !need_install {
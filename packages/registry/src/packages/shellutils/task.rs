@@ -4,22 +4,17 @@ pub fn verify() -> cu::Result<Verified> {
     check_in_shaft!("task");
     check_in_shaft!("x");
     let v = command_output!("task", ["--version"]);
-    check_outdated!(v.trim(), metadata[task]::VERSION);
-    Ok(Verified::UpToDate)
+    let version_info = check_outdated!(v.trim(), metadata[task]::VERSION);
+    Ok(Verified::UpToDate(Some(version_info)))
 }
 
 pub fn download(ctx: &Context) -> cu::Result<()> {
-    hmgr::download_file(
-        task_filename(),
-        task_url()?,
-        metadata::task::SHA(),
-        ctx.bar(),
-    )?;
+    ctx.download_file(task_filename(), task_url()?, metadata::task::SHA())?;
     Ok(())
 }
 
 pub fn install(ctx: &Context) -> cu::Result<()> {
-    if let Ok(Verified::UpToDate) = verify() {
+    if let Ok(Verified::UpToDate(_)) = verify() {
         return Ok(());
     }
     let install_dir = ctx.install_dir();
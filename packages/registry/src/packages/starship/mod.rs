@@ -5,13 +5,13 @@ use crate::pre::*;
 register_binaries!("starship");
 version_cache!(static CFG_VERSION = metadata::starship::CFG_VERSION);
 binary_dependencies!(CargoBinstall);
-config_dependencies!(Terminal); // for clink
+optional_dependencies!(Terminal); // for clink
 
 pub fn verify(_: &Context) -> cu::Result<Verified> {
     let v = check_cargo!("starship");
-    check_outdated!(&v.version, metadata[starship]::VERSION);
+    let version_info = check_outdated!(&v.version, metadata[starship]::VERSION);
     check_config_version_cache!(CFG_VERSION);
-    Ok(Verified::UpToDate)
+    Ok(Verified::UpToDate(Some(version_info)))
 }
 
 pub fn install(ctx: &Context) -> cu::Result<()> {
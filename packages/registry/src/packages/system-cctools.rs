@@ -21,7 +21,7 @@ pub fn verify(_: &Context) -> cu::Result<Verified> {
     cu::warn!(
         "system-cctools does not check if a working C/C++ Toolchain and tools exists, please check so manually if it does not work"
     );
-    Ok(Verified::UpToDate)
+    Ok(Verified::UpToDate(None))
 }
 
 pub fn install(_: &Context) -> cu::Result<()> {
@@ -5,7 +5,7 @@ register_binaries!("git");
 
 pub fn verify(_: &Context) -> cu::Result<Verified> {
     check_in_path!("git");
-    Ok(Verified::UpToDate)
+    Ok(Verified::UpToDate(None))
 }
 
 pub fn install(ctx: &Context) -> cu::Result<()> {
@@ -6,7 +6,7 @@ register_binaries!("java", "javac");
 pub fn verify(_: &Context) -> cu::Result<Verified> {
     check_in_path!("java");
     check_in_path!("javac");
-    Ok(Verified::UpToDate)
+    Ok(Verified::UpToDate(None))
 }
 
 pub fn install(ctx: &Context) -> cu::Result<()> {
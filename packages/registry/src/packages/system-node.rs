@@ -6,7 +6,7 @@ register_binaries!("node", "npm");
 pub fn verify(_: &Context) -> cu::Result<Verified> {
     check_in_path!("node");
     check_in_path!("npm");
-    Ok(Verified::UpToDate)
+    Ok(Verified::UpToDate(None))
 }
 
 pub fn install(ctx: &Context) -> cu::Result<()> {
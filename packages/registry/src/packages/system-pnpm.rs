@@ -5,7 +5,7 @@ register_binaries!("pnpm");
 
 pub fn verify(_: &Context) -> cu::Result<Verified> {
     check_in_path!("pnpm");
-    Ok(Verified::UpToDate)
+    Ok(Verified::UpToDate(None))
 }
 
 pub fn install(ctx: &Context) -> cu::Result<()> {
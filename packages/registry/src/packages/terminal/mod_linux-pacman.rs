@@ -7,12 +7,12 @@ version_cache!(static CFG_VERSION = metadata::hyprland::kitty::CFG_VERSION);
 
 pub fn verify(_: &Context) -> cu::Result<Verified> {
     let version = check_pacman!("ttf-hack-nerd");
-    check_outdated!(version.trim(), metadata[hack_font]::VERSION_PACMAN);
+    let version_info = check_outdated!(version.trim(), metadata[hack_font]::VERSION_PACMAN);
     let version = check_pacman!("kitty");
     check_outdated!(version.trim(), metadata[hyprland::kitty]::VERSION);
 
     check_config_version_cache!(CFG_VERSION);
-    Ok(Verified::UpToDate)
+    Ok(Verified::UpToDate(Some(version_info)))
 }
 pub fn install(ctx: &Context) -> cu::Result<()> {
     epkg::pacman::install_many(
@@ -6,10 +6,10 @@ version_cache!(static CFG_VERSION = metadata::terminal::CONFIG_VERSION);
 
 pub fn verify(_: &Context) -> cu::Result<Verified> {
     let version = check_homebrew!("font-hack-nerd-font");
-    check_outdated!(version.trim(), metadata[hack_font]::VERSION_HOMEBREW);
+    let version_info = check_outdated!(version.trim(), metadata[hack_font]::VERSION_HOMEBREW);
 
     check_config_version_cache!(CFG_VERSION);
-    Ok(Verified::UpToDate)
+    Ok(Verified::UpToDate(Some(version_info)))
 }
 
 pub fn install(ctx: &Context) -> cu::Result<()> {
@@ -5,7 +5,7 @@ use crate::pre::*;
 register_binaries!("clink-cmd");
 version_cache!(static CFG_VERSION = metadata::terminal::CONFIG_VERSION);
 binary_dependencies!(_7z, Cmake); // used to compile clink-cmd
-config_dependencies!(Pwsh, Git);
+optional_dependencies!(Pwsh, Git);
 
 mod windows_clink;
 mod windows_font;
@@ -16,7 +16,7 @@ pub fn verify(ctx: &Context) -> cu::Result<Verified> {
     check_verified!(windows_clink::verify(ctx)?);
     check_config_version_cache!(CFG_VERSION);
 
-    Ok(Verified::UpToDate)
+    Ok(Verified::UpToDate(None))
 }
 
 pub fn download(ctx: &Context) -> cu::Result<()> {
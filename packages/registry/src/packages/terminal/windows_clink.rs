@@ -11,18 +11,17 @@ pub fn verify(ctx: &Context) -> cu::Result<Verified> {
     let clink_bat = clink_bat.into_utf8()?;
     let v = command_output!("cmd", ["/c", &clink_bat, "--version"]);
     let v = v.trim();
-    check_outdated!(v, metadata[terminal::clink]::VERSION);
+    let version_info = check_outdated!(v, metadata[terminal::clink]::VERSION);
 
     check_version_cache!(WRAPPER_VERSION);
-    Ok(Verified::UpToDate)
+    Ok(Verified::UpToDate(Some(version_info)))
 }
 
 pub fn download(ctx: &Context) -> cu::Result<()> {
-    hmgr::download_file(
+    ctx.download_file(
         "clink.zip",
         metadata::terminal::clink::URL,
         metadata::terminal::clink::SHA,
-        ctx.bar(),
     )?;
     Ok(())
 }
@@ -4,15 +4,14 @@ version_cache!(static VERSION = metadata::hack_font::VERSION);
 
 pub fn verify() -> cu::Result<Verified> {
     check_version_cache!(VERSION);
-    Ok(Verified::UpToDate)
+    Ok(Verified::UpToDate(None))
 }
 
 pub fn download(ctx: &Context) -> cu::Result<()> {
-    hmgr::download_file(
+    ctx.download_file(
         "hack-nerd-font.zip",
         font_download_url(),
         metadata::hack_font::SHA,
-        ctx.bar(),
     )?;
     Ok(())
 }
@@ -26,6 +25,10 @@ fn font_download_url() -> String {
 pub fn install(setting_path: &Path) -> cu::Result<()> {
     cu::info!("installing hack nerd font...");
     if opfs::ensure_terminated("notepad.exe").is_err() {
+        interactive::ensure_input(
+            "wait for notepad.exe to be closed",
+            "close notepad.exe before installing the hack nerd font",
+        )?;
         let _ = cu::prompt!(
             "detected notepad.exe is currently open - please close it, then press ENTER to continue"
         );
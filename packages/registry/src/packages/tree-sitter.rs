@@ -6,16 +6,15 @@ pub fn verify(_: &Context) -> cu::Result<Verified> {
     check_in_shaft!("tree-sitter");
     let v = command_output!("tree-sitter", ["--version"]);
     let v = v.strip_prefix("tree-sitter ").unwrap_or(&v).trim();
-    check_outdated!(v, metadata[tree_sitter]::VERSION);
-    Ok(Verified::UpToDate)
+    let version_info = check_outdated!(v, metadata[tree_sitter]::VERSION);
+    Ok(Verified::UpToDate(Some(version_info)))
 }
 pub fn download(ctx: &Context) -> cu::Result<()> {
     let file_name = tree_sitter_base_name()?;
-    hmgr::download_file(
+    ctx.download_file(
         format!("{file_name}.gz"),
         tree_sitter_url()?,
         metadata::tree_sitter::SHA(),
-        ctx.bar(),
     )?;
     Ok(())
 }
@@ -15,22 +15,17 @@ pub fn verify(_: &Context) -> cu::Result<Verified> {
     let version_parsed = version_parsed.split('-').take(3).join("-");
     if version_parsed.is_empty() {
         cu::warn!("failed to parse vcpkg version, raw output:\n{version}");
-        return Ok(Verified::NotUpToDate);
+        return Ok(Verified::NotUpToDate(None));
     }
 
-    check_outdated!(&version_parsed, metadata[vcpkg]::VERSION);
+    let version_info = check_outdated!(&version_parsed, metadata[vcpkg]::VERSION);
     // we don't check the version of the repo as it could be on a commit that's intentionally
     // overriden by the current user
-    Ok(Verified::UpToDate)
+    Ok(Verified::UpToDate(Some(version_info)))
 }
 
 pub fn download(ctx: &Context) -> cu::Result<()> {
-    hmgr::download_file(
-        "vcpkg.exe",
-        download_url(),
-        metadata::vcpkg::SHA(),
-        ctx.bar(),
-    )?;
+    ctx.download_file("vcpkg.exe", download_url(), metadata::vcpkg::SHA())?;
     Ok(())
 }
 
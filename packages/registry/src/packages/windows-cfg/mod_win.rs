@@ -7,7 +7,7 @@ pub fn verify(ctx: &Context) -> cu::Result<Verified> {
             "windows-cfg is a pseudo package for documenting Windows configuration, use `shaft config windows-cfg` to read"
         );
     }
-    Ok(Verified::UpToDate)
+    Ok(Verified::UpToDate(None))
 }
 
 pub fn install(_: &Context) -> cu::Result<()> {
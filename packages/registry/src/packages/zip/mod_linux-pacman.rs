@@ -5,10 +5,10 @@ register_binaries!("zip", "unzip");
 
 pub fn verify(_: &Context) -> cu::Result<Verified> {
     let v = check_pacman!("zip");
-    check_outdated!(&v, metadata[coreutils::zip]::VERSION);
+    let version_info = check_outdated!(&v, metadata[coreutils::zip]::VERSION);
     let v = check_pacman!("unzip");
     check_outdated!(&v, metadata[coreutils::unzip]::VERSION);
-    Ok(Verified::UpToDate)
+    Ok(Verified::UpToDate(Some(version_info)))
 }
 
 pub fn install(ctx: &Context) -> cu::Result<()> {
@@ -7,7 +7,7 @@ binary_dependencies!(Git);
 pub fn verify(_: &Context) -> cu::Result<Verified> {
     check_in_shaft!("zip");
     check_in_shaft!("unzip");
-    Ok(Verified::UpToDate)
+    Ok(Verified::UpToDate(None))
 }
 
 pub fn install(ctx: &Context) -> cu::Result<()> {
@@ -0,0 +1,118 @@
+use std::path::{Path, PathBuf};
+
+use corelib::{Version, hmgr};
+use cu::pre::*;
+
+use crate::{BinId, PkgId};
+
+impl BinId {
+    /// Get the version of the binary provided by `provider`, from the output of `<bin> --version`.
+    ///
+    /// For `system-*` providers, the binary is found in PATH outside of the shaft's binary
+    /// directories. For other providers, the binary in the shaft's binary directories is used.
+    ///
+    /// Returns `None` if the binary is not found, or the output does not have a version
+    pub fn version_from(self, provider: PkgId) -> Option<String> {
+        let Some(path) = find_binary(self, provider) else {
+            cu::debug!("cannot find '{self}' provided by '{provider}'");
+            return None;
+        };
+        let stdout = match probe_version_output(&path) {
+            Ok(x) => x,
+            Err(e) => {
+                cu::debug!("failed to get version of '{}': {e:?}", path.display());
+                return None;
+            }
+        };
+        let version = parse_version_output(&stdout);
+        cu::debug!("version of '{}': {version:?}", path.display());
+        version
+    }
+}
+
+/// Check if the version of a binary is `min_version` or newer.
+/// Versions that cannot be compared are not new enough
+pub fn satisfies_min_version(version: &str, min_version: &str) -> bool {
+    !Version(version).lt(min_version)
+}
+
+fn find_binary(bin: BinId, provider: PkgId) -> Option<PathBuf> {
+    let file = format!("{bin}{}", std::env::consts::EXE_SUFFIX);
+    let shaft_dirs = [hmgr::paths::bin_root(), hmgr::paths::sbin_root()];
+    if !provider.to_str().starts_with("system-") {
+        return shaft_dirs
+            .iter()
+            .map(|dir| dir.join(&file))
+            .find(|x| x.is_file());
+    }
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .filter(|dir| !shaft_dirs.contains(dir))
+        .map(|dir| dir.join(&file))
+        .find(|x| x.is_file())
+}
+
+fn probe_version_output(path: &Path) -> cu::Result<String> {
+    let (child, stdout) = path
+        .command()
+        .arg("--version")
+        .stdout(cu::pio::string())
+        .stdie_null()
+        .spawn()?;
+    child.wait_nz()?;
+    stdout.join()?
+}
+
+/// Find the first word that looks like a version on the first line,
+/// for example `v20.11.0` or `Python 3.11.4`
+fn parse_version_output(stdout: &str) -> Option<String> {
+    let line = stdout.lines().next()?;
+    line.split_whitespace()
+        .map(|word| {
+            word.trim_start_matches(['v', 'V'])
+                .trim_end_matches([',', ';'])
+        })
+        .find(|word| word.starts_with(|c: char| c.is_ascii_digit()))
+        .map(|word| word.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_version_output() {
+        assert_eq!(
+            parse_version_output("v20.11.0\n").as_deref(),
+            Some("20.11.0")
+        );
+        assert_eq!(
+            parse_version_output("Python 3.11.4").as_deref(),
+            Some("3.11.4")
+        );
+        assert_eq!(
+            parse_version_output("git version 2.43.0, built today\nmore").as_deref(),
+            Some("2.43.0")
+        );
+        assert_eq!(
+            parse_version_output("cmake version 3.28.1;\n\nCMake suite").as_deref(),
+            Some("3.28.1")
+        );
+        // only the first line is checked
+        assert_eq!(parse_version_output("no version here\n1.0.0"), None);
+        assert_eq!(parse_version_output(""), None);
+    }
+
+    #[test]
+    fn test_satisfies_min_version() {
+        assert!(satisfies_min_version("20.11.0", "20"));
+        assert!(satisfies_min_version("20", "20"));
+        assert!(satisfies_min_version("22.1.0", "20"));
+        assert!(satisfies_min_version("3.11.4", "3.11"));
+        assert!(satisfies_min_version("3.12.0", "3.11"));
+        assert!(!satisfies_min_version("18.19.0", "20"));
+        assert!(!satisfies_min_version("3.9.18", "3.11"));
+        // not comparable
+        assert!(!satisfies_min_version("3.x", "3.11"));
+    }
+}
@@ -1,9 +1,9 @@
 use std::cell::{RefCell, RefMut};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use corelib::hmgr::config::ConfigDef;
-use corelib::hmgr::{self, Item, ItemMgr};
+use corelib::hmgr::{self, Item, ItemMgr, PlannedDownload};
 use cu::pre::*;
 use enumset::EnumSet;
 
@@ -18,6 +18,8 @@ pub struct Context {
     items: RefCell<ItemMgr>,
     bar: Option<Arc<cu::ProgressBar>>,
     installed: EnumSet<PkgId>,
+    /// Downloads recorded instead of performed, see [`plan_downloads`](Self::plan_downloads)
+    download_plan: Option<RefCell<Vec<PlannedDownload>>>,
 }
 impl Context {
     pub fn new(items: ItemMgr) -> Self {
@@ -27,6 +29,7 @@ impl Context {
             items: RefCell::new(items),
             bar: None,
             installed: EnumSet::default(),
+            download_plan: None,
         }
     }
     pub fn pkg_name(&self) -> &'static str {
@@ -60,6 +63,52 @@ impl Context {
         self.bar.as_ref()
     }
 
+    /// Download URL to the download cache, and return the path to the downloaded file.
+    ///
+    /// See [`hmgr::download_file`]. When planning downloads, the path is returned
+    /// without downloading anything
+    pub fn download_file(
+        &self,
+        identifier: impl AsRef<Path>,
+        url: impl AsRef<str>,
+        sha256_checksum: impl AsRef<str>,
+    ) -> cu::Result<PathBuf> {
+        let Some(plan) = &self.download_plan else {
+            return hmgr::download_file(identifier, url, sha256_checksum, self.bar());
+        };
+        let download = PlannedDownload::new(identifier, url, sha256_checksum);
+        let path = download.path.clone();
+        if !path.exists() {
+            cu::debug!(
+                "planned download: {} from {}",
+                download.identifier.display(),
+                download.url
+            );
+            plan.borrow_mut().push(download);
+        }
+        Ok(path)
+    }
+
+    /// Run `f` with this context without downloading anything.
+    ///
+    /// Calls to [`download_file`](Self::download_file) inside `f` return the path
+    /// in the download cache immediately. Files that are not in the cache yet are
+    /// recorded and returned. Checksums of cached files are not checked
+    pub fn plan_downloads(
+        &mut self,
+        f: impl FnOnce(&Self) -> cu::Result<()>,
+    ) -> cu::Result<Vec<PlannedDownload>> {
+        self.download_plan = Some(RefCell::new(vec![]));
+        let result = f(self);
+        let planned = self
+            .download_plan
+            .take()
+            .map(RefCell::into_inner)
+            .unwrap_or_default();
+        result?;
+        Ok(planned)
+    }
+
     pub fn temp_dir(&self) -> PathBuf {
         hmgr::paths::temp_dir(self.pkg_name())
     }
@@ -146,3 +195,43 @@ impl From<u8> for Stage {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_plan_downloads() {
+        hmgr::paths::init_test_home();
+        let url = "https://example.invalid/test-plan-downloads.zip";
+        let mut ctx = Context::new(ItemMgr::default());
+        let planned = ctx
+            .plan_downloads(|ctx| {
+                let path = ctx.download_file("test-plan-downloads.zip", url, "ABCD")?;
+                assert_eq!(path, hmgr::paths::download("test-plan-downloads.zip", url));
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(planned.len(), 1);
+        assert_eq!(planned[0].url, url);
+        assert_eq!(planned[0].sha256_checksum, "abcd");
+        assert!(ctx.download_plan.is_none());
+        // nothing is downloaded or created
+        let path = hmgr::paths::download("test-plan-downloads.zip", url);
+        assert!(!path.exists());
+        assert!(!hmgr::paths::download_root().exists());
+
+        // files already in the cache are not planned
+        cu::fs::write(&path, "test").unwrap();
+        let planned = ctx
+            .plan_downloads(|ctx| {
+                ctx.download_file("test-plan-downloads.zip", url, "ABCD")
+                    .map(|_| ())
+            })
+            .unwrap();
+        assert!(planned.is_empty());
+        assert_eq!(cu::fs::read_string(&path).unwrap(), "test");
+
+        cu::fs::remove(&path).unwrap();
+    }
+}
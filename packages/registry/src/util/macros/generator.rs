@@ -5,9 +5,14 @@ macro_rules! register_binaries {
 pub(crate) use register_binaries;
 
 /// Convenience macro to generate implementation for binary_dependencies
+///
+/// A minimum version can be required with `>=`, for example `binary_dependencies!(Git, Node >= "20")`
 macro_rules! binary_dependencies {
-    ($($ident:ident),* $(,)?) => {
+    ($($ident:ident $(>= $version:literal)?),* $(,)?) => {
         pub fn binary_dependencies() -> EnumSet<BinId> { enum_set! { $(BinId::$ident)|* } }
+        pub fn binary_dependency_versions() -> &'static [(BinId, &'static str)] {
+            &[ $($( (BinId::$ident, $version), )?)* ]
+        }
     };
 }
 pub(crate) use binary_dependencies;
@@ -20,6 +25,24 @@ macro_rules! config_dependencies {
 }
 pub(crate) use config_dependencies;
 
+/// Convenience macro to generate implementation for optional_dependencies
+macro_rules! optional_dependencies {
+    ($($ident:ident),* $(,)?) => {
+        pub fn optional_dependencies() -> EnumSet<PkgId> { enum_set! { $(PkgId::$ident)|* } }
+    };
+}
+pub(crate) use optional_dependencies;
+
+/// Convenience macro to generate implementation for conflicts
+#[allow(unused)]
+macro_rules! conflicts {
+    ($($ident:ident),* $(,)?) => {
+        pub fn conflicts() -> EnumSet<PkgId> { enum_set! { $(PkgId::$ident)|* } }
+    };
+}
+#[allow(unused)]
+pub(crate) use conflicts;
+
 /// Generate a static VERSION_CACHE constant
 macro_rules! version_cache {
     (pub static $ident:ident = $expr:expr) => {
@@ -31,7 +54,7 @@ macro_rules! version_cache {
 }
 pub(crate) use version_cache;
 
-/// Generate config definition. This also generates the config_location function
+/// Generate config definition. This also generates the config_location and config_def functions
 macro_rules! config_file {
     (static $config_ident:ident : $config_ty:ty = {
         template: $template_str:expr,
@@ -40,6 +63,9 @@ macro_rules! config_file {
         pub fn config_location(ctx: &Context) -> cu::Result<Option<PathBuf>> {
             Ok(Some(ctx.config_file()))
         }
+        pub fn config_def() -> Option<corelib::hmgr::config::UntypedConfigDef> {
+            Some($config_ident.untyped())
+        }
         static $config_ident: ConfigDef<$config_ty> = ConfigDef::new(
             $template_str, &[$($migration_script_str),*]
         );
@@ -142,23 +142,36 @@ macro_rules! check_homebrew {
 #[cfg(target_os = "macos")]
 pub(crate) use check_homebrew;
 
-/// Check actual version is at least as new as expected version
+/// Check actual version is at least as new as expected version.
+///
+/// Returns `NotUpToDate` with the versions if outdated, otherwise evaluates to the
+/// [`VersionInfo`](crate::VersionInfo), to be returned with `UpToDate`
 macro_rules! check_outdated {
     ($actual:expr, metadata [ $($package:ident)::* ]:: $($expected:tt)*) => {{
         let a = $actual;
         let e = metadata::$($package)::*::$($expected)*;
-        if Version(a).lt(e) {
+        let version = $crate::VersionInfo {
+            current: a.to_string(),
+            expected: AsRef::<str>::as_ref(&e).to_string(),
+        };
+        if Version(a).lt(&e) {
             cu::error!("verify: {} {} is outdated, new version: {}", stringify!($($package).*), a, e);
-            return Ok(Verified::NotUpToDate);
+            return Ok(Verified::NotUpToDate(Some(version)));
         }
+        version
     }};
     ($actual:expr, $expected:expr) => {{
         let a = $actual;
         let e = $expected;
-        if Version(a).lt(e) {
+        let version = $crate::VersionInfo {
+            current: a.to_string(),
+            expected: AsRef::<str>::as_ref(&e).to_string(),
+        };
+        if Version(a).lt(&e) {
             cu::error!("verify: {} {} is outdated, new version: {}", stringify!($expected), a, e);
-            return Ok(Verified::NotUpToDate);
+            return Ok(Verified::NotUpToDate(Some(version)));
         }
+        version
     }};
 }
 pub(crate) use check_outdated;
@@ -167,7 +180,7 @@ pub(crate) use check_outdated;
 macro_rules! check_verified {
     ($sub:expr) => {{
         let v = $sub;
-        if v != Verified::UpToDate {
+        if !matches!(v, Verified::UpToDate(_)) {
             cu::debug!("check_verified: for '{}': {:?}", stringify!($sub), v);
             return Ok(v);
         }
@@ -187,7 +200,7 @@ macro_rules! check_version_cache {
             }
             Some(false) => {
                 cu::error!("verify: {} is bumped: {}", cache.id(), cache.version());
-                return Ok(Verified::NotUpToDate);
+                return Ok(Verified::NotUpToDate(None));
             }
             _ => {}
         }
@@ -223,7 +236,7 @@ pub(crate) use check_config_version_cache;
 macro_rules! verify_config {
     ($($s:tt)*) => {{
         match (|| -> cu::Result<Verified> { $($s)* })() {
-            Ok(Verified::UpToDate) => Ok(Verified::UpToDate),
+            Ok(Verified::UpToDate(x)) => Ok(Verified::UpToDate(x)),
             Ok(_) => Ok(Verified::NeedsConfig),
             Err(x) => Err(x)
         }
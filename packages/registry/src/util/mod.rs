@@ -4,4 +4,6 @@ mod package;
 pub use package::*;
 mod context;
 pub use context::*;
+mod bin_version;
+pub use bin_version::*;
 pub mod macros;
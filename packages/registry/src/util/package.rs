@@ -1,5 +1,6 @@
 use std::path::PathBuf;
 
+use corelib::hmgr::config::UntypedConfigDef;
 use corelib::opfs;
 use cu::pre::*;
 use enumset::EnumSet;
@@ -43,11 +44,15 @@ pub struct Package {
     pub(crate) uninstall_fn: fn(&Context) -> cu::Result<()>,
     // optional functions
     pub(crate) binary_dependencies_fn: fn() -> EnumSet<BinId>,
+    pub(crate) binary_dependency_versions_fn: fn() -> &'static [(BinId, &'static str)],
     pub(crate) config_dependencies_fn: fn() -> EnumSet<PkgId>,
+    pub(crate) optional_dependencies_fn: fn() -> EnumSet<PkgId>,
+    pub(crate) conflicts_fn: fn() -> EnumSet<PkgId>,
     pub(crate) download_fn: fn(&Context) -> cu::Result<()>,
     pub(crate) configure_fn: fn(&Context) -> cu::Result<()>,
     pub(crate) clean_fn: fn(&Context) -> cu::Result<()>,
     pub(crate) config_location_fn: fn(&Context) -> cu::Result<Option<PathBuf>>,
+    pub(crate) config_def_fn: fn() -> Option<UntypedConfigDef>,
     pub(crate) backup_fn: fn(&Context) -> cu::Result<()>,
     pub(crate) restore_fn: fn(&Context) -> cu::Result<()>,
     pub(crate) pre_uninstall_fn: fn(&Context) -> cu::Result<()>,
@@ -95,6 +100,16 @@ impl Package {
         (self.binary_dependencies_fn)()
     }
 
+    /// Get the minimum version required for a binary dependency, if any.
+    /// Declared with `>=` in `binary_dependencies!`
+    #[inline(always)]
+    pub fn binary_dependency_version(&self, bin: BinId) -> Option<&'static str> {
+        (self.binary_dependency_versions_fn)()
+            .iter()
+            .find(|(b, _)| *b == bin)
+            .map(|(_, v)| *v)
+    }
+
     /// Get the config dependencies for this package
     ///
     /// For each config dependency:
@@ -108,6 +123,23 @@ impl Package {
         (self.config_dependencies_fn)()
     }
 
+    /// Get the optional dependencies for this package
+    ///
+    /// These are packages that enhance this package if present. They are offered
+    /// (but not required) when installing this package, and are treated like
+    /// config dependencies when installed
+    #[inline(always)]
+    pub fn optional_dependencies(&self) -> EnumSet<PkgId> {
+        (self.optional_dependencies_fn)()
+    }
+
+    /// Get the packages that cannot be installed together with this package,
+    /// in addition to the ones providing the same binaries
+    #[inline(always)]
+    pub fn conflicts(&self) -> EnumSet<PkgId> {
+        (self.conflicts_fn)()
+    }
+
     /// Verify the package is installed and up-to-date
     #[inline(always)]
     pub fn verify(&self, ctx: &Context) -> cu::Result<Verified> {
@@ -187,6 +219,12 @@ impl Package {
         (self.config_location_fn)(ctx)
     }
 
+    /// Get the definition of the config file, if the package declares one with `config_file!`
+    #[inline(always)]
+    pub fn config_def(&self) -> Option<UntypedConfigDef> {
+        (self.config_def_fn)()
+    }
+
     /// Backup the package content to prepare for remove or update
     #[inline(always)]
     #[cu::context("failed to backup '{}'", ctx.pkg)]
@@ -203,9 +241,12 @@ impl Package {
         Ok(PackageRestoreGuard::new(self, ctx))
     }
 
+    /// Restore the backup taken with [`backup`](Self::backup).
+    ///
+    /// Normally done by the restore guard, unless the process died before the guard could run
     #[inline(always)]
     #[cu::context("failed to restore '{}'", ctx.pkg)]
-    fn restore(&self, ctx: &Context) -> cu::Result<()> {
+    pub fn restore(&self, ctx: &Context) -> cu::Result<()> {
         (self.restore_fn)(ctx)
     }
 }
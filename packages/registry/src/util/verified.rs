@@ -1,10 +1,10 @@
 /// Package verification status
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Verified {
-    /// Everything is up-to-date
-    UpToDate,
-    /// Installed but not up-to-date
-    NotUpToDate,
+    /// Everything is up-to-date. Has the versions if compared with `check_outdated!`
+    UpToDate(Option<VersionInfo>),
+    /// Installed but not up-to-date. Has the versions if compared with `check_outdated!`
+    NotUpToDate(Option<VersionInfo>),
     /// Not installed
     NotInstalled,
     /// Installation is up-to-date, but needs to be re-configured
@@ -13,9 +13,53 @@ pub enum Verified {
 impl Verified {
     pub const fn is_uptodate(uptodate: bool) -> Self {
         if uptodate {
-            Self::UpToDate
+            Self::UpToDate(None)
         } else {
-            Self::NotUpToDate
+            Self::NotUpToDate(None)
         }
     }
+
+    /// Get the installed and expected versions, if they were compared when verifying
+    pub fn version(&self) -> Option<&VersionInfo> {
+        match self {
+            Self::UpToDate(x) | Self::NotUpToDate(x) => x.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+/// Installed and expected versions compared with `check_outdated!` when verifying
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VersionInfo {
+    /// The installed version
+    pub current: String,
+    /// The version in the registry
+    pub expected: String,
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pre::*;
+
+    fn verify(current: &str) -> cu::Result<Verified> {
+        let version_info = check_outdated!(current, "1.2.0");
+        Ok(Verified::UpToDate(Some(version_info)))
+    }
+
+    #[test]
+    fn test_check_outdated() {
+        let verified = verify("1.10.0").unwrap();
+        assert!(matches!(verified, Verified::UpToDate(_)));
+        let version = verified.version().unwrap();
+        assert_eq!(version.current, "1.10.0");
+        assert_eq!(version.expected, "1.2.0");
+
+        let verified = verify("1.1.9").unwrap();
+        assert!(matches!(verified, Verified::NotUpToDate(_)));
+        let version = verified.version().unwrap();
+        assert_eq!(version.current, "1.1.9");
+        assert_eq!(version.expected, "1.2.0");
+
+        assert!(Verified::NotInstalled.version().is_none());
+    }
 }
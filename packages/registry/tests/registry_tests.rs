@@ -1,5 +1,9 @@
+use std::collections::BTreeMap;
+
+use cu::pre::*;
 use enum_map::Enum as _;
-use shaft_registry::PkgId;
+use enumset::EnumSet;
+use shaft_registry::{GroupId, PkgId};
 
 #[test]
 fn test_registry_in_sync() {
@@ -23,3 +27,33 @@ fn test_descriptions_for_all_packages() {
         );
     }
 }
+
+#[derive(Deserialize)]
+struct GroupDef {
+    packages: Vec<String>,
+}
+
+#[test]
+fn test_groups_in_sync() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/groups.toml");
+    let groups: BTreeMap<String, GroupDef> =
+        toml::parse(&cu::fs::read_string(path).unwrap()).unwrap();
+    assert_eq!(groups.len(), EnumSet::<GroupId>::all().len());
+    for (name, def) in &groups {
+        let group = GroupId::from_str(name).unwrap();
+        assert_eq!(group.to_str(), name);
+        assert!(
+            !group.description().is_empty(),
+            "group '{name}' is missing docs"
+        );
+        // packages not enabled on the current platform are left out
+        let expected = def
+            .packages
+            .iter()
+            .map(|x| PkgId::from_str(x).unwrap())
+            .filter(|x| x.package().enabled())
+            .collect::<EnumSet<_>>();
+        assert_eq!(group.packages(), expected, "group '{name}'");
+    }
+    assert_eq!(GroupId::from_str("@web"), None);
+}